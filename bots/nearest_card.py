@@ -0,0 +1,21 @@
+#!/usr/bin/env python3
+
+# a sample external bot for WarO: bids the card nearest to the prize card.
+# reads one JSON message per line on stdin; replies to "bid_request" on stdout,
+# echoing the request's round.
+
+import json
+import sys
+
+hand = []
+
+for line in sys.stdin:
+    message = json.loads(line)
+    kind = message["type"]
+
+    if kind == "deal" or kind == "prize_revealed":
+        hand = message["hand"]
+    elif kind == "bid_request":
+        prize_card = message["prize_card"]
+        bid = min(hand, key=lambda card: abs(card - prize_card))
+        print(json.dumps({"round": message["round"], "bid": bid}), flush=True)
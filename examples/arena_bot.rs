@@ -0,0 +1,53 @@
+// a reference bot for the WarO arena, protocol version 1 (see ARENA.md).
+// it bids the card nearest to the prize card.
+//
+// usage: cargo run --example arena_bot [address] [name]
+
+use serde_json::{json, Value};
+use std::env;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+
+const PROTOCOL_VERSION: u32 = 1;
+
+fn nearest_card(prize_card: u64, hand: &[u64]) -> u64 {
+    *hand.iter().min_by_key(|card| (**card as i64 - prize_card as i64).abs()).unwrap()
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let address = args.get(1).map(|s| s.as_str()).unwrap_or("127.0.0.1:7880");
+    let name = args.get(2).map(|s| s.as_str()).unwrap_or("nearest-bot");
+
+    let stream = TcpStream::connect(address).expect("Unable to connect");
+    stream.set_nodelay(true).unwrap();
+    let mut writer = stream.try_clone().unwrap();
+    let register = json!({"type": "register", "name": name, "protocol": PROTOCOL_VERSION});
+    writeln!(writer, "{}", register).unwrap();
+
+    let mut hand: Vec<u64> = vec![];
+    for line in BufReader::new(stream).lines() {
+        let message: Value = match line.ok().and_then(|l| serde_json::from_str(&l).ok()) {
+            Some(message) => message,
+            None => break,
+        };
+
+        match message["type"].as_str().unwrap_or("") {
+            "registered" => println!("registered as {}", message["name"]),
+            "rejected" => {
+                println!("rejected: {}", message["reason"]);
+                break;
+            }
+            "tourney_start" => println!("table {}: {}", message["table"], message["players"]),
+            "deal" | "prize_revealed" => {
+                hand = message["hand"].as_array().unwrap().iter().filter_map(|c| c.as_u64()).collect();
+            }
+            "bid_request" => {
+                let bid = nearest_card(message["prize_card"].as_u64().unwrap(), &hand);
+                writeln!(writer, "{}", json!({"round": message["round"], "bid": bid})).unwrap();
+            }
+            "tourney_over" => println!("tourney won by {}", message["winner"]),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// uses waro as a library: a strategy written in Rust plays a tourney
+// against two built-in strategies.
+//
+// usage: cargo run --example custom_strategy
+
+use waro::game;
+use waro::strategy::{Bidder, Strategy};
+use waro::{Config, Hand, Player, Table};
+
+// bids the lowest card that still beats the prize, else the lowest card
+#[derive(Debug)]
+struct JustEnough;
+
+impl Bidder for JustEnough {
+    fn select_card(&mut self, prize_card: u32, hand: &Hand, _highest_card: u32) -> Option<u32> {
+        let above = hand.cards.iter().filter(|card| **card > prize_card).min();
+        above.or_else(|| hand.cards.iter().min()).copied()
+    }
+}
+
+fn main() {
+    let players = vec![
+        Player::new_with_strategy("just_enough", Strategy::new_custom("just_enough", Box::new(JustEnough))),
+        Player::new_from_json("bach", "hybrid_card"),
+        Player::new_from_json("liszt", "nearest_card"),
+    ];
+    let config = Config::new(3, 10, 40);
+    let mut table = Table{players, .. Table::new()};
+
+    game::play_tourney(&config, &mut table);
+}
@@ -0,0 +1,249 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::config;
+use crate::config::Config;
+use crate::engine::{GameState, FORFEIT_OFFER};
+use crate::game;
+use crate::game::Table;
+use crate::interrupt;
+
+// how a strategy bids, over every round it played
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Profile {
+    // prize card -> bid -> how often
+    pub bids: BTreeMap<u32, BTreeMap<u32, u32>>,
+    pub bids_on_low_prizes: u32,
+    // a card from the top third of the deck bid on a prize from the bottom third
+    pub high_cards_wasted: u32,
+    pub rounds_won: u32,
+    // winning bid minus the next highest, summed over the rounds won
+    pub total_margin: u32,
+    pub points_captured: u32,
+    // the face value of every card bid
+    pub cards_spent: u32,
+    // rounds given up without a card (an illegal bid or a disqualified seat), left out of the rest
+    pub forfeits: u32,
+}
+
+impl Profile {
+    pub fn wasted_rate(&self) -> f64 {
+        if self.bids_on_low_prizes == 0 { 0.0 } else { self.high_cards_wasted as f64 / self.bids_on_low_prizes as f64 }
+    }
+
+    pub fn average_margin(&self) -> f64 {
+        if self.rounds_won == 0 { 0.0 } else { self.total_margin as f64 / self.rounds_won as f64 }
+    }
+
+    pub fn points_per_card_spent(&self) -> f64 {
+        if self.cards_spent == 0 { 0.0 } else { self.points_captured as f64 / self.cards_spent as f64 }
+    }
+}
+
+// the bid at a fraction of the way through the bids on one prize
+fn quantile(counts: &BTreeMap<u32, u32>, fraction: f64) -> u32 {
+    let total: u32 = counts.values().sum();
+    let target = (fraction * (total.max(1) - 1) as f64).round() as u32;
+    let mut seen = 0;
+    for (bid, count) in counts {
+        seen += count;
+        if seen > target {
+            return *bid;
+        }
+    }
+    0
+}
+
+// profiles by strategy name; players with the same strategy are counted together
+#[derive(Debug)]
+pub struct Analysis {
+    pub num_cards: u32,
+    pub num_games: u32,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Analysis {
+    pub fn new(num_cards: u32) -> Analysis {
+        Analysis{num_cards, num_games: 0, profiles: BTreeMap::new()}
+    }
+
+    fn is_low_prize(&self, prize_card: u32) -> bool { prize_card <= self.num_cards / 3 }
+    fn is_high_card(&self, card: u32) -> bool { card > self.num_cards * 2 / 3 }
+
+    // strategies holds the strategy of each seat in the finished game
+    pub fn record_game(&mut self, strategies: &[&str], state: &GameState) {
+        self.num_games += 1;
+        for round in state.rounds() {
+            for (seat, bid) in round.bids.iter().enumerate() {
+                let low_prize = self.is_low_prize(round.prize_card);
+                let high_card = self.is_high_card(*bid);
+                let profile = self.profiles.entry(String::from(strategies[seat])).or_default();
+                if *bid == FORFEIT_OFFER {
+                    profile.forfeits += 1;
+                    continue;
+                }
+                *profile.bids.entry(round.prize_card).or_default().entry(*bid).or_insert(0) += 1;
+                profile.cards_spent += bid;
+                if low_prize {
+                    profile.bids_on_low_prizes += 1;
+                    profile.high_cards_wasted += u32::from(high_card);
+                }
+                if round.winner == Some(seat) {
+                    let next_highest = round.bids.iter().enumerate().filter(|(other, _)| *other != seat).map(|(_, b)| *b).max().unwrap_or(0);
+                    profile.rounds_won += 1;
+                    profile.total_margin += bid.saturating_sub(next_highest);
+                    profile.points_captured += round.prize_card;
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "analysis of {} games; low prizes are 1-{}, high cards {}-{}",
+                 self.num_games, self.num_cards / 3, self.num_cards * 2 / 3 + 1, self.num_cards)?;
+        for (strategy, profile) in &self.profiles {
+            writeln!(f, "\n{}:", strategy)?;
+            if profile.forfeits > 0 {
+                writeln!(f, "  rounds forfeited: {}", profile.forfeits)?;
+            }
+            writeln!(f, "  high cards wasted on low prizes: {} of {} bids ({:.1}%)",
+                     profile.high_cards_wasted, profile.bids_on_low_prizes, 100.0 * profile.wasted_rate())?;
+            writeln!(f, "  average winning margin: {:.2} over {} rounds won", profile.average_margin(), profile.rounds_won)?;
+            writeln!(f, "  points captured per card spent: {:.3} ({} points for {})",
+                     profile.points_per_card_spent(), profile.points_captured, profile.cards_spent)?;
+            writeln!(f, "  bids by prize (min / 25% / median / 75% / max, mean):")?;
+            for (prize_card, counts) in &profile.bids {
+                let total: u32 = counts.values().sum();
+                let mean = counts.iter().map(|(bid, count)| bid * count).sum::<u32>() as f64 / total as f64;
+                writeln!(f, "    {:>3}: {:>3} / {:>3} / {:>3} / {:>3} / {:>3}, {:.1}", prize_card,
+                         quantile(counts, 0.0), quantile(counts, 0.25), quantile(counts, 0.5),
+                         quantile(counts, 0.75), quantile(counts, 1.0), mean)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// plays the tourney as any other, with ratings, checkpoints and Ctrl-C, profiling every bid
+pub fn run(config: &Config, table: &mut Table) -> Analysis {
+    table.analysis = Some(Analysis::new(config.num_cards));
+    game::play_tourney(config, table);
+    table.analysis.take().unwrap_or_else(|| Analysis::new(config.num_cards))
+}
+
+pub fn play(config_file: &str) {
+    let (mut table, config) = match config::build_from_json(config_file) {
+        Ok(built) => built,
+        Err(e) => {
+            println!("analysis cannot start: {}", e);
+            return;
+        }
+    };
+    interrupt::install();
+    let analysis = run(&config, &mut table);
+    println!("\n\n{}", analysis);
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use crate::engine::Hand;
+    use crate::player::Player;
+
+    #[test]
+    fn test_record_game_basic() {
+        // 12 cards: low prizes are 1-4, high cards 9-12; prizes come off the end of the kitty
+        let kitty = Hand{cards: vec![10, 2]};
+        let hands = vec![Hand{cards: vec![12, 3]}, Hand{cards: vec![11, 1]}];
+        let mut state = GameState::from_deal(kitty, hands, 12);
+        for bids in [[12, 1], [3, 11]] {
+            state.reveal_prize();
+            state.submit_bid(0, bids[0]).unwrap();
+            state.submit_bid(1, bids[1]).unwrap();
+            state.resolve_round();
+        }
+        let mut analysis = Analysis::new(12);
+
+        // test
+        analysis.record_game(&["max_card", "min_card"], &state);
+
+        let max_card = &analysis.profiles["max_card"];
+        assert_eq!(max_card.bids_on_low_prizes, 1);
+        assert_eq!(max_card.high_cards_wasted, 1);
+        assert_eq!(max_card.rounds_won, 1);
+        assert_eq!(max_card.total_margin, 11);
+        assert_eq!(max_card.points_captured, 2);
+        assert_eq!(max_card.cards_spent, 15);
+        let min_card = &analysis.profiles["min_card"];
+        assert_eq!(min_card.points_captured, 10);
+        assert_eq!(min_card.average_margin(), 8.0);
+        assert_eq!(min_card.bids[&10][&11], 1);
+    }
+
+    #[test]
+    fn test_record_game_forfeit() {
+        let kitty = Hand{cards: vec![10, 2]};
+        let hands = vec![Hand{cards: vec![12, 3]}, Hand{cards: vec![11, 1]}];
+        let mut state = GameState::from_deal(kitty, hands, 12);
+        state.reveal_prize();
+        state.submit_bid(0, 12).unwrap();
+        state.forfeit(1).unwrap();
+        state.resolve_round();
+        let mut analysis = Analysis::new(12);
+
+        // test
+        analysis.record_game(&["max_card", "min_card"], &state);
+
+        let min_card = &analysis.profiles["min_card"];
+        assert_eq!(min_card.forfeits, 1);
+        assert!(min_card.bids.is_empty());
+        assert_eq!(min_card.bids_on_low_prizes, 0);
+        assert!(analysis.to_string().contains("rounds forfeited: 1"));
+        assert_eq!(analysis.profiles["max_card"].total_margin, 12);
+    }
+
+    #[test]
+    fn test_quantile_basic() {
+        let counts: BTreeMap<u32, u32> = vec![(2, 1), (5, 2), (9, 1)].into_iter().collect();
+
+        // test
+        let result: Vec<u32> = [0.0, 0.5, 1.0].iter().map(|f| quantile(&counts, *f)).collect();
+
+        assert_eq!(result, vec![2, 5, 9]);
+    }
+
+    #[test]
+    fn test_run_counts_every_bid() {
+        let players = vec![Player::new_from_json("mozart", "nearest_card"), Player::new_from_json("salieri", "nearest_card"),
+                           Player::new_from_json("haydn", "hybrid_card")];
+        let mut table = Table{players, .. Table::new()};
+        let config = Config::new(3, 4, 20);
+
+        // test
+        let result = run(&config, &mut table);
+
+        assert_eq!(result.num_games, 4);
+        let nearest: u32 = result.profiles["nearest_card"].bids.values().flat_map(|b| b.values()).sum();
+        // 5 rounds a game, two players
+        assert_eq!(nearest, 4 * 5 * 2);
+    }
+
+    #[test]
+    fn test_run_saves_ratings() {
+        let file = std::env::temp_dir().join(format!("waro_analyze_ratings_{}.json", std::process::id()));
+        let file = String::from(file.to_string_lossy());
+        let players = vec![Player::new_from_json("mozart", "max_card"), Player::new_from_json("salieri", "min_card")];
+        let ratings = crate::ratings::Ratings::load(serde_json::from_str(&format!(r#"{{"file": {:?}}}"#, file)).unwrap()).unwrap();
+        let mut table = Table{players, ratings: Some(ratings), .. Table::new()};
+        let config = Config::new(2, 3, 12);
+
+        // test
+        run(&config, &mut table);
+
+        let saved = std::fs::read_to_string(&file);
+        let _ = std::fs::remove_file(&file);
+        assert!(saved.unwrap().contains("mozart"));
+    }
+}
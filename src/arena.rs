@@ -0,0 +1,361 @@
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::TryFrom;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+use crate::game;
+use crate::game::Table;
+use crate::player::Player;
+use crate::strategy::Strategy;
+use crate::strategy::external;
+use crate::strategy::external::{ExternalBot, Message, PROTOCOL_VERSION};
+
+pub const DEFAULT_ADDRESS: &str = "0.0.0.0:7880";
+const REGISTER_TIMEOUT_MS: u64 = 5000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArenaConfig {
+    pub num_cards: u32,
+    pub num_games: u32,
+    pub table_size: u32,
+    #[serde(default = "external::default_timeout_ms")]
+    pub timeout_ms: u64,
+    #[serde(default = "default_results_file")]
+    pub results_file: String,
+}
+
+fn default_results_file() -> String { String::from("arena_results.jsonl") }
+
+// the first line a bot sends: {"type": "register", "name": "py-bot", "protocol": 2}
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BotMessage {
+    Register { name: String, protocol: u32 },
+}
+
+// one line of the results file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TourneyResult {
+    pub table: u64,
+    pub players: Vec<String>,
+    pub winner: String,
+    pub games_won: Vec<u32>,
+    pub finished_at: u64,
+}
+
+enum Event {
+    Registered(String, ExternalBot),
+    TableDone(TourneyResult, Vec<(String, ExternalBot)>),
+}
+
+pub fn build_from_json(config_file: &str) -> ArenaConfig {
+    let data = fs::read_to_string(config_file).expect("Unable to read file");
+    serde_json::from_str(&data).unwrap()
+}
+
+pub fn serve(config_file: &str, address: &str) {
+    let arena_config = build_from_json(config_file);
+    let listener = TcpListener::bind(address).expect("Unable to listen");
+    tracer!("TRACER arena on {} (protocol {})", listener.local_addr().unwrap(), PROTOCOL_VERSION);
+    run(listener, &arena_config, None);
+}
+
+// runs until `max_tourneys` tourneys have finished, or forever
+pub fn run(listener: TcpListener, arena_config: &ArenaConfig, max_tourneys: Option<usize>) -> Vec<TourneyResult> {
+    let (sender, events) = mpsc::channel();
+    let names = Arc::new(Mutex::new(HashSet::new()));
+
+    let registrar = sender.clone();
+    let taken = Arc::clone(&names);
+    thread::spawn(move || accept_bots(listener, registrar, taken));
+
+    schedule(arena_config, sender, events, names, max_tourneys)
+}
+
+// each bot registers on its own thread, so one slow to say hello holds up nobody else
+fn accept_bots(listener: TcpListener, sender: Sender<Event>, names: Arc<Mutex<HashSet<String>>>) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let sender = sender.clone();
+        let names = Arc::clone(&names);
+        thread::spawn(move || match register(stream, &names) {
+            Ok((name, bot)) => {
+                tracer!("TRACER arena registered {}", name);
+                let _ = sender.send(Event::Registered(name, bot));
+            }
+            Err(e) => tracer!("TRACER arena rejected a bot: {}", e),
+        });
+    }
+}
+
+fn register(stream: TcpStream, names: &Mutex<HashSet<String>>) -> Result<(String, ExternalBot), String> {
+    stream.set_read_timeout(Some(Duration::from_millis(REGISTER_TIMEOUT_MS))).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| e.to_string())?;
+    reader.get_ref().set_read_timeout(None).map_err(|e| e.to_string())?;
+
+    let (name, protocol) = match serde_json::from_str(line.trim()) {
+        Ok(BotMessage::Register{name, protocol}) => (name, protocol),
+        Err(_) => {
+            let _ = external::write_message(reader.get_mut(), &Message::Rejected{reason: "expected a register message"});
+            return Err(format!("bad register: {:?}", line));
+        }
+    };
+    if protocol != PROTOCOL_VERSION {
+        let reason = format!("unsupported protocol {}, this arena speaks {}", protocol, PROTOCOL_VERSION);
+        let _ = external::write_message(reader.get_mut(), &Message::Rejected{reason: &reason});
+        return Err(reason);
+    }
+    let mut bot = ExternalBot::from_socket(reader, 0).map_err(|e| e.to_string())?;
+
+    // names are unique while a bot is connected: a second "py-bot" becomes "py-bot#2"
+    let mut names = names.lock().unwrap();
+    let mut unique = name.clone();
+    let mut suffix = 2;
+    while names.contains(&unique) {
+        unique = format!("{}#{}", name, suffix);
+        suffix += 1;
+    }
+    names.insert(unique.clone());
+
+    bot.send(&Message::Registered{name: &unique, protocol: PROTOCOL_VERSION}).map_err(|e| e.to_string())?;
+    Ok((unique, bot))
+}
+
+// seats waiting bots first come, first served; a bot goes back in line after each tourney
+fn schedule(arena_config: &ArenaConfig, sender: Sender<Event>, events: Receiver<Event>,
+            names: Arc<Mutex<HashSet<String>>>, max_tourneys: Option<usize>) -> Vec<TourneyResult> {
+    let table_size = arena_config.table_size as usize;
+    let mut lobby: VecDeque<(String, ExternalBot)> = VecDeque::new();
+    let mut results = vec![];
+    let mut next_table = 1;
+    let mut tables_started = 0;
+
+    while max_tourneys.is_none_or(|max| results.len() < max) {
+        match events.recv() {
+            Ok(Event::Registered(name, bot)) => lobby.push_back((name, bot)),
+            Ok(Event::TableDone(result, seats)) => {
+                record(&arena_config.results_file, &result);
+                results.push(result);
+                emit_leaderboard(&results);
+                for (name, mut bot) in seats {
+                    if bot.is_alive() {
+                        lobby.push_back((name, bot));
+                    } else {
+                        tracer!("TRACER arena lost {}", name);
+                        names.lock().unwrap().remove(&name);
+                    }
+                }
+            }
+            Err(_) => break,
+        }
+
+        if max_tourneys.is_some_and(|max| tables_started >= max) || table_size == 0 {
+            continue;
+        }
+        while let Some(seats) = take_seats(&mut lobby, table_size, &names) {
+            let table_config = arena_config.clone();
+            let done = sender.clone();
+            let table_id = next_table;
+            thread::spawn(move || {
+                let (result, seats) = play_table(table_id, &table_config, seats);
+                let _ = done.send(Event::TableDone(result, seats));
+            });
+            next_table += 1;
+            tables_started += 1;
+            if max_tourneys.is_some_and(|max| tables_started >= max) {
+                break;
+            }
+        }
+    }
+
+    results
+}
+
+// bots that hung up while waiting in the lobby give up their place in line
+fn take_seats(lobby: &mut VecDeque<(String, ExternalBot)>, table_size: usize,
+              names: &Mutex<HashSet<String>>) -> Option<Vec<(String, ExternalBot)>> {
+    if lobby.len() < table_size {
+        return None;
+    }
+    lobby.retain_mut(|(name, bot)| {
+        let alive = bot.is_alive();
+        if !alive {
+            tracer!("TRACER arena lost {}", name);
+            names.lock().unwrap().remove(name.as_str());
+        }
+        alive
+    });
+    if lobby.len() < table_size {
+        return None;
+    }
+    Some(lobby.drain(..table_size).collect())
+}
+
+fn play_table(table_id: u64, arena_config: &ArenaConfig, seats: Vec<(String, ExternalBot)>) -> (TourneyResult, Vec<(String, ExternalBot)>) {
+    let names: Vec<String> = seats.iter().map(|(name, _)| name.clone()).collect();
+    let mut players = vec![];
+    for (name, mut bot) in seats {
+        let _ = bot.send(&Message::TourneyStart{table: table_id, players: &names, num_games: arena_config.num_games});
+        let mut player = Player::new_with_strategy(&name, Strategy::new_remote(arena_config.timeout_ms));
+        player.take_seat(bot.with_timeout(arena_config.timeout_ms));
+        players.push(player);
+    }
+
+    let num_players = u32::try_from(players.len()).unwrap();
+    let config = Config::new(num_players, arena_config.num_games, arena_config.num_cards);
+    let mut table = Table{players, .. Table::new()};
+    let winner = game::run_tourney(&config, &mut table);
+
+    let games_won = table.players.iter().map(|p| p.num_games_won()).collect();
+    let finished_at = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let result = TourneyResult{table: table_id, players: names, winner, games_won, finished_at};
+
+    let seats = table.players.iter_mut()
+                     .filter_map(|p| p.leave_seat().map(|bot| (String::from(p.name()), bot)))
+                     .collect();
+    (result, seats)
+}
+
+fn record(results_file: &str, result: &TourneyResult) {
+    let file = OpenOptions::new().create(true).append(true).open(results_file);
+    match file {
+        Ok(mut file) => {
+            let _ = writeln!(file, "{}", serde_json::to_string(result).unwrap());
+        }
+        Err(e) => tracer!("TRACER arena could not record results: {}", e),
+    }
+}
+
+fn emit_leaderboard(results: &[TourneyResult]) {
+    let mut wins: HashMap<&str, u32> = HashMap::new();
+    for result in results {
+        for name in &result.players {
+            wins.entry(name).or_insert(0);
+        }
+        *wins.entry(&result.winner).or_insert(0) += 1;
+    }
+    let mut standings: Vec<(&str, u32)> = wins.into_iter().collect();
+    standings.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    println!("arena leaderboard after {} tourneys:", results.len());
+    for (name, won) in standings {
+        println!("  {} {}", name, won);
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::net::SocketAddr;
+
+    // registers, bids the first card of its hand, and returns every message it received
+    #[allow(dead_code)]
+    fn test_bot(address: SocketAddr, name: &str, protocol: u32, num_tourneys: usize) -> Vec<Value> {
+        let stream = TcpStream::connect(address).unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        writeln!(writer, r#"{{"type": "register", "name": "{}", "protocol": {}}}"#, name, protocol).unwrap();
+
+        let mut messages = vec![];
+        let mut hand: Vec<u64> = vec![];
+        let mut tourneys = 0;
+        for line in BufReader::new(stream).lines() {
+            let message: Value = serde_json::from_str(&line.unwrap()).unwrap();
+            match message["type"].as_str().unwrap() {
+                "deal" | "prize_revealed" => {
+                    hand = message["hand"].as_array().unwrap().iter().map(|c| c.as_u64().unwrap()).collect();
+                }
+                "bid_request" => writeln!(writer, r#"{{"round": {}, "bid": {}}}"#, message["round"], hand[0]).unwrap(),
+                "tourney_over" => tourneys += 1,
+                _ => {}
+            }
+            let done = message["type"] == "rejected" || tourneys == num_tourneys;
+            messages.push(message);
+            if done {
+                break;
+            }
+        }
+        messages
+    }
+
+    #[test]
+    fn test_arena_plays_tourneys() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let results_file = std::env::temp_dir().join(format!("waro_arena_{}.jsonl", std::process::id()));
+        let arena_config = ArenaConfig{num_cards: 9, num_games: 2, table_size: 2, timeout_ms: 2000,
+                                       results_file: results_file.to_string_lossy().into_owned()};
+        let bots: Vec<_> = (0..2).map(|_| thread::spawn(move || test_bot(address, "bot", PROTOCOL_VERSION, 2))).collect();
+
+        // test
+        let results = run(listener, &arena_config, Some(2));
+
+        assert_eq!(results.len(), 2);
+        let mut players = results[0].players.clone();
+        players.sort();
+        assert_eq!(players, vec!["bot", "bot#2"]);
+        assert_eq!(results[0].games_won.iter().sum::<u32>(), 2);
+        for bot in bots {
+            let messages = bot.join().unwrap();
+            assert_eq!(messages[0]["type"], "registered");
+            assert_eq!(messages.iter().filter(|m| m["type"] == "tourney_start").count(), 2);
+        }
+        let recorded = fs::read_to_string(&results_file).unwrap();
+        assert_eq!(recorded.lines().count(), 2);
+        let _ = fs::remove_file(&results_file);
+    }
+
+    #[test]
+    fn test_take_seats_skips_dead_bots() {
+        // a bot hangs up when the other end of the pipe it reads from is closed
+        let (dead_reader, hung_up) = std::io::pipe().unwrap();
+        let (alive_reader, _connected) = std::io::pipe().unwrap();
+        let (_, dead_writer) = std::io::pipe().unwrap();
+        let (_, alive_writer) = std::io::pipe().unwrap();
+        drop(hung_up);
+        let dead = ExternalBot::from_pipes(dead_reader, dead_writer, 0);
+        let alive = ExternalBot::from_pipes(alive_reader, alive_writer, 0);
+        let names = Mutex::new(vec![String::from("dead"), String::from("alive")].into_iter().collect());
+        let mut lobby: VecDeque<(String, ExternalBot)> = vec![(String::from("dead"), dead), (String::from("alive"), alive)].into_iter().collect();
+        thread::sleep(Duration::from_millis(50));
+
+        // test
+        let result = take_seats(&mut lobby, 2, &names);
+
+        assert!(result.is_none());
+        assert_eq!(lobby.len(), 1);
+        assert_eq!(lobby[0].0, "alive");
+        assert!(!names.lock().unwrap().contains("dead"));
+    }
+
+    #[test]
+    fn test_register_wrong_protocol() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let bot = thread::spawn(move || test_bot(address, "old", PROTOCOL_VERSION + 1, 1));
+        let names = Mutex::new(HashSet::new());
+        let (stream, _) = listener.accept().unwrap();
+
+        // test
+        let result = register(stream, &names);
+
+        assert!(result.is_err());
+        let messages = bot.join().unwrap();
+        assert_eq!(messages[0]["type"], "rejected");
+    }
+}
@@ -0,0 +1,207 @@
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+
+use crate::config::Config;
+use crate::game;
+use crate::game::Table;
+
+const DEFAULT_EVERY_GAMES: u32 = 100;
+
+// "checkpoint": {"file": "checkpoint.json", "every_games": 100} in a config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpointing {
+    pub file: String,
+    #[serde(default = "default_every_games")]
+    pub every_games: u32,
+}
+
+fn default_every_games() -> u32 { DEFAULT_EVERY_GAMES }
+
+#[derive(Serialize)]
+struct SavedTourney<'a> {
+    config: &'a Config,
+    table: &'a Table,
+}
+
+#[derive(Deserialize)]
+struct LoadedTourney {
+    config: Config,
+    table: Table,
+}
+
+// written to a scratch file first, so an interrupted save leaves the last checkpoint intact
+pub fn save(file: &str, config: &Config, table: &Table) -> io::Result<()> {
+    let data = serde_json::to_string_pretty(&SavedTourney{config, table})?;
+    let scratch = format!("{}.tmp", file);
+    fs::write(&scratch, data)?;
+    fs::rename(&scratch, file)
+}
+
+pub fn save_if_due(config: &Config, table: &Table) {
+    let checkpointing = match &table.checkpoint {
+        Some(checkpointing) => checkpointing,
+        None => return,
+    };
+    let due = table.games_played.is_multiple_of(checkpointing.every_games.max(1)) || table.games_played == game::tourney_length(config, table);
+    if due {
+        save_now(config, table);
+    }
+}
+
+// returns whether a checkpoint was written
+pub fn save_now(config: &Config, table: &Table) -> bool {
+    let checkpointing = match &table.checkpoint {
+        Some(checkpointing) => checkpointing,
+        None => return false,
+    };
+    match save(&checkpointing.file, config, table) {
+        Ok(()) => {
+            table_tracer!(table, "TRACER checkpoint after game {} in {}", table.games_played, checkpointing.file);
+            true
+        }
+        Err(e) => {
+            tracer!("TRACER could not save checkpoint: {}", e);
+            false
+        }
+    }
+}
+
+// the tourney continues from the next game, with the deals it would have had.
+// external bots are started again; a remote seat has nobody to reconnect it, and
+// a custom strategy cannot be rebuilt from a file, so either one is an error
+pub fn load(file: &str) -> Result<(Table, Config), String> {
+    let data = fs::read_to_string(file).map_err(|e| e.to_string())?;
+    let LoadedTourney{config, mut table} = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+    if let Some(player) = table.players.iter().find(|p| p.awaits_connection()) {
+        return Err(format!("{} plays a remote seat, which cannot be resumed", player.name()));
+    }
+    let hot_seat = table.hot_seat;
+    for player in &mut table.players {
+        player.start()?;
+        player.set_hot_seat(hot_seat);
+    }
+    Ok((table, config))
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use crate::config;
+    use crate::game;
+    use crate::player::{Player, PlayerStats};
+    use crate::strategy::{Bidder, Strategy};
+    use crate::engine::Hand;
+
+    #[allow(dead_code)]
+    const DATA: &str = r#"{"num_cards": 30, "num_games": 6, "seed": 42,
+                           "players": [{"name": "bach", "strategy": "hybrid_card"},
+                                       {"name": "liszt", "strategy": "nearest_card"},
+                                       {"name": "chopin", "strategy": "max_card"}]}"#;
+
+    #[allow(dead_code)]
+    fn standings(table: &Table) -> Vec<(String, PlayerStats)> {
+        table.players.iter().map(|p| (p.name.clone(), p.player_stats.clone())).collect()
+    }
+
+    #[allow(dead_code)]
+    fn scratch_file(name: &str) -> String {
+        let file = std::env::temp_dir().join(format!("waro_{}_{}.json", name, std::process::id()));
+        String::from(file.to_string_lossy())
+    }
+
+    #[test]
+    fn test_resume_matches_uninterrupted() {
+        let (mut whole, config) = config::build_from_str(DATA).unwrap();
+        game::run_tourney(&config, &mut whole);
+        let (mut first_half, config) = config::build_from_str(DATA).unwrap();
+        for _game in 0..3 {
+            game::play_next_game(&config, &mut first_half);
+        }
+        let file = std::env::temp_dir().join(format!("waro_checkpoint_{}.json", std::process::id()));
+        let file = file.to_string_lossy();
+        save(&file, &config, &first_half).unwrap();
+
+        // test
+        let (mut resumed, config) = load(&file).unwrap();
+        game::run_tourney(&config, &mut resumed);
+
+        assert_eq!(resumed.games_played, 6);
+        assert_eq!(standings(&resumed), standings(&whole));
+        let _ = fs::remove_file(&*file);
+    }
+
+    #[test]
+    fn test_deal_follows_seed() {
+        let (mut a, config) = config::build_from_str(DATA).unwrap();
+        let (mut b, _) = config::build_from_str(DATA).unwrap();
+        a.games_played = 4;
+        b.games_played = 4;
+
+        // test
+        game::deal_to_table(&config, &mut a);
+        game::deal_to_table(&config, &mut b);
+
+        let kitty = |table: &Table| table.game.as_ref().unwrap().kitty().clone();
+        assert_eq!(kitty(&a), kitty(&b));
+        b.games_played = 5;
+        game::deal_to_table(&config, &mut b);
+        assert_ne!(kitty(&a), kitty(&b));
+    }
+
+    #[test]
+    fn test_load_rejects_remote_seat() {
+        let (mut table, config) = config::build_from_str(DATA).unwrap();
+        table.players[1] = Player::new_with_strategy("liszt", Strategy::new_remote(1000));
+        let file = scratch_file("remote");
+        save(&file, &config, &table).unwrap();
+
+        // test
+        let result = load(&file);
+
+        assert_eq!(result.err(), Some(String::from("liszt plays a remote seat, which cannot be resumed")));
+        let _ = fs::remove_file(&file);
+    }
+
+    // bids its first card
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct First;
+
+    impl Bidder for First {
+        fn select_card(&mut self, _prize_card: u32, hand: &Hand, _highest_card: u32) -> Option<u32> {
+            hand.cards.first().copied()
+        }
+    }
+
+    #[test]
+    fn test_load_rejects_custom_strategy() {
+        let (mut table, config) = config::build_from_str(DATA).unwrap();
+        table.players[1] = Player::new_with_strategy("liszt", Strategy::new_custom("first", Box::new(First)));
+        let file = scratch_file("custom");
+        save(&file, &config, &table).unwrap();
+
+        // test
+        let result = load(&file);
+
+        assert!(result.unwrap_err().contains("the custom strategy \"first\" cannot be loaded"));
+        let _ = fs::remove_file(&file);
+    }
+
+    #[test]
+    fn test_load_starts_external_bots() {
+        let (mut table, config) = config::build_from_str(DATA).unwrap();
+        // loading a strategy starts nothing, so this bad command is only found by load
+        let unstarted: Strategy = serde_json::from_str(r#"{"name": "external", "command": ["./no/such/bot"]}"#).unwrap();
+        table.players[1] = Player::new_with_strategy("liszt", unstarted);
+        let file = scratch_file("external");
+        save(&file, &config, &table).unwrap();
+
+        // test
+        let result = load(&file);
+
+        assert!(result.unwrap_err().starts_with("unable to start external bot"));
+        let _ = fs::remove_file(&file);
+    }
+}
@@ -0,0 +1,94 @@
+
+use serde_json::Value;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+
+use crate::engine::Hand;
+use crate::strategy::console;
+
+// a terminal client for a "remote" seat on a `waro serve` table
+pub fn join(address: &str, name: &str) {
+    let stream = TcpStream::connect(address).expect("Unable to connect");
+    stream.set_nodelay(true).unwrap();
+    let mut writer = stream.try_clone().unwrap();
+    let join = serde_json::json!({"type": "join", "name": name});
+    writeln!(writer, "{}", join).expect("Unable to join");
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut hand = Hand::new();
+
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(_) => continue,
+        };
+
+        match message["type"].as_str().unwrap_or("") {
+            "seated" => println!("seated as {}", text(&message["name"])),
+            "rejected" => println!("rejected: {}", text(&message["reason"])),
+            "game_start" => println!("\nnew game: {} players, {} cards", message["num_players"], message["num_cards"]),
+            "deal" | "prize_revealed" => {
+                hand = Hand{cards: cards(&message["hand"])};
+                if message["type"] == "prize_revealed" {
+                    println!("\nprize_card: {}", message["prize_card"]);
+                    println!("your hand: {}", hand);
+                }
+            }
+            "bid_request" => {
+                println!("enter your pick within {} seconds:", message["timeout_ms"].as_u64().unwrap_or(0) / 1000);
+                let bid = match read_bid(&mut input, &hand) {
+                    Some(bid) => bid,
+                    None => break,
+                };
+                if writeln!(writer, "{}", serde_json::json!({"round": message["round"], "bid": bid})).is_err() {
+                    break;
+                }
+            }
+            "round_result" => {
+                let bids: Vec<String> = message["bids"].as_array().unwrap_or(&vec![]).iter()
+                                                       .map(|b| format!("{} {}", text(&b["name"]), b["offer"])).collect();
+                println!("bids: {}; {} wins {}", bids.join(", "), text(&message["winner"]), message["prize_card"]);
+            }
+            "game_over" => {
+                for score in message["scores"].as_array().unwrap_or(&vec![]) {
+                    println!("{}: {}", text(&score["name"]), score["total"]);
+                }
+                println!("game over. WINNER: {}", text(&message["winner"]));
+            }
+            _ => {}
+        }
+    }
+    println!("disconnected.");
+}
+
+fn text(value: &Value) -> String {
+    match value.as_str() {
+        Some(s) => String::from(s),
+        None => value.to_string(),
+    }
+}
+
+fn cards(value: &Value) -> Vec<u32> {
+    value.as_array().unwrap_or(&vec![]).iter().filter_map(|c| c.as_u64()).map(|c| c as u32).collect()
+}
+
+// None once stdin is closed
+fn read_bid<R: BufRead>(input: &mut R, hand: &Hand) -> Option<u32> {
+    loop {
+        let mut pick = String::new();
+        match input.read_line(&mut pick) {
+            Ok(0) | Err(_) => return None,
+            Ok(_) => {}
+        }
+        match console::validate_pick(&pick, hand) {
+            Some(card) => return Some(card),
+            None => println!("'{}' is not a card in your hand", pick.trim()),
+        }
+    }
+}
@@ -1,15 +1,19 @@
 
-use serde_json;
 use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use std::fs;
 
-use player::game::Table;
-use player::Player;
+use crate::checkpoint::Checkpointing;
+use crate::duplicate::Duplicate;
+use crate::engine::builtin;
+use crate::game::{Seating, Table};
+use crate::player::{Penalty, Player};
+use crate::ratings::{Ratings, RatingsConfig};
+use crate::spectator::Feed;
+use crate::strategy;
+use crate::strategy::Strategy;
 
-pub mod player;
-
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub num_players: u32,
     pub num_games: u32,
@@ -17,40 +21,159 @@ pub struct Config {
     pub num_cards_per_hand: u32,
 }
 
+impl Config {
+    // the kitty is dealt a hand too, and leftover cards sit out
+    pub fn new(num_players: u32, num_games: u32, num_cards: u32) -> Config {
+        let num_cards_per_hand = num_cards / (num_players + 1);
+        Config{num_players, num_games, num_cards, num_cards_per_hand}
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct JsonConfiguration {
     num_cards: u32,
     num_games: u32,
-    players: Vec<JsonPlayer>
+    players: Vec<JsonPlayer>,
+    #[serde(default)]
+    illegal_bid_penalty: Penalty,
+    // defaults to on when more than one player is at the console
+    #[serde(default)]
+    hot_seat: Option<bool>,
+    #[serde(default)]
+    spectators: Option<JsonSpectators>,
+    // deals are random unless a seed is given; the seed in use is printed at the start
+    #[serde(default)]
+    seed: Option<u64>,
+    #[serde(default)]
+    checkpoint: Option<Checkpointing>,
+    #[serde(default)]
+    ratings: Option<RatingsConfig>,
+    #[serde(default)]
+    duplicate: Option<Duplicate>,
+    #[serde(default)]
+    seating: Option<Seating>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct JsonPlayer {
-    name: String,
-    strategy: String,
+struct JsonSpectators {
+    address: String,
+    // spectators never see the cards in hand
+    #[serde(default)]
+    hide_hands: bool,
+    // how far behind the live game the feed runs
+    #[serde(default)]
+    delay_ms: u64,
 }
 
-pub fn build_from_json(config_file: &str) -> (Table, Config) {
-    let data = fs::read_to_string(config_file).expect("Unable to read file");
-    let json_configuration: JsonConfiguration = serde_json::from_str(&data).unwrap();
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct JsonPlayer {
+    pub(crate) name: String,
+    pub(crate) strategy: String,
+    // only used by the "external" strategy
+    #[serde(default)]
+    command: Vec<String>,
+    // used by the "external" and "remote" strategies
+    #[serde(default = "strategy::external::default_timeout_ms")]
+    timeout_ms: u64,
+    // only used by the "console" strategy
+    #[serde(default)]
+    coach: Option<String>,
+}
+
+impl JsonPlayer {
+    pub(crate) fn into_player(self) -> Result<Player, String> {
+        let strategy = match self.strategy.as_str() {
+            strategy::EXTERNAL => Strategy::new_external(&self.command, self.timeout_ms)?,
+            strategy::REMOTE | strategy::API => {
+                let mut strategy = Strategy::new_remote(self.timeout_ms);
+                strategy.name = self.strategy.clone();
+                strategy
+            }
+            name if name == strategy::CONSOLE || builtin::NAMES.contains(&name) => Strategy::new(name),
+            name => return Err(format!("{} has an unknown strategy {:?}", self.name, name)),
+        };
+        let strategy = match &self.coach {
+            Some(coach) if !builtin::NAMES.contains(&coach.as_str()) => {
+                return Err(format!("{} has an unknown coach {:?}", self.name, coach));
+            }
+            Some(coach) => strategy.with_coach(coach),
+            None => strategy,
+        };
+        Ok(Player::new_with_strategy(&self.name, strategy))
+    }
+}
+
+// every player and the kitty need at least one card
+pub(crate) fn check_num_cards(num_cards: u32, num_players: usize) -> Result<(), String> {
+    if (num_cards as usize) < num_players + 1 {
+        return Err(format!("num_cards must be at least {} for {} players and the kitty", num_players + 1, num_players));
+    }
+    Ok(())
+}
+
+pub fn build_from_json(config_file: &str) -> Result<(Table, Config), String> {
+    let data = fs::read_to_string(config_file).map_err(|e| format!("unable to read {}: {}", config_file, e))?;
+    build_from_str(&data)
+}
+
+pub fn build_from_str(data: &str) -> Result<(Table, Config), String> {
+    let json_configuration: JsonConfiguration = serde_json::from_str(data).map_err(|e| e.to_string())?;
+    if json_configuration.players.is_empty() {
+        return Err(String::from("at least one player is required"));
+    }
+    if json_configuration.num_games == 0 {
+        return Err(String::from("num_games must be at least 1"));
+    }
+    check_num_cards(json_configuration.num_cards, json_configuration.players.len())?;
+    // stats, ratings and remote seats all go by name
+    for (i, player) in json_configuration.players.iter().enumerate() {
+        if json_configuration.players[..i].iter().any(|p| p.name == player.name) {
+            return Err(format!("player name {:?} appears more than once", player.name));
+        }
+    }
+    // a duplicate tourney hands out the deals by seat, so moving the players as well would undo it
+    if json_configuration.duplicate.is_some() && json_configuration.seating.is_some() {
+        return Err(String::from("duplicate and seating cannot be used together"));
+    }
+    // every game of the tourney is counted in a u32, replays included
+    if let Some(duplicate) = &json_configuration.duplicate {
+        let num_players = json_configuration.players.len();
+        let tourney_length = duplicate.checked_num_replays(num_players).and_then(|n| n.checked_mul(json_configuration.num_games));
+        if tourney_length.is_none() {
+            return Err(format!("too many games: {} deals, each replayed for {} players", json_configuration.num_games, num_players));
+        }
+    }
 
     let mut players: Vec<Player> = vec![];
 
     for json_player in json_configuration.players {
-        let player = Player::new_from_json(&json_player.name, &json_player.strategy);
-        players.push(player);
+        players.push(json_player.into_player()?);
+    }
+
+    let num_humans = players.iter().filter(|p| p.is_human()).count();
+    let hot_seat = json_configuration.hot_seat.unwrap_or(num_humans > 1);
+    for player in &mut players {
+        player.set_hot_seat(hot_seat);
     }
 
     let num_players = u32::try_from(players.len()).unwrap();
-    let table = Table{players: players, .. Table::new()};
+    let feed = match &json_configuration.spectators {
+        Some(spectators) => Some(Feed::start(&spectators.address, spectators.hide_hands, spectators.delay_ms)
+                                     .map_err(|e| format!("unable to start spectator feed: {}", e))?),
+        None => None,
+    };
+    let ratings = match json_configuration.ratings {
+        Some(ratings_config) => Some(Ratings::load(ratings_config)?),
+        None => None,
+    };
+    let seed = json_configuration.seed.unwrap_or_else(rand::random);
+    let table = Table{players, penalty: json_configuration.illegal_bid_penalty, hot_seat, feed, seed,
+                      checkpoint: json_configuration.checkpoint, ratings,
+                      duplicate: json_configuration.duplicate, seating: json_configuration.seating, .. Table::new()};
 
-    let num_games = json_configuration.num_games;
-    let num_cards = json_configuration.num_cards;
-    let num_cards_per_hand = num_cards / (num_players + 1);
-    let config = Config{num_players: num_players, num_games: num_games,
-                        num_cards: num_cards, num_cards_per_hand: num_cards_per_hand};
+    let config = Config::new(num_players, json_configuration.num_games, json_configuration.num_cards);
 
-    (table, config)
+    Ok((table, config))
 }
 
 #[allow(unused_imports)]
@@ -63,4 +186,120 @@ mod tests {
         let b = 5;
         assert_eq!(a, b);
     }
+
+    #[test]
+    fn test_build_from_str_basic() {
+        let data = r#"{"num_cards": 12, "num_games": 3,
+                       "players": [{"name": "bach", "strategy": "next_card"}, {"name": "liszt", "strategy": "max_card"}]}"#;
+        // test
+        let (table, config) = build_from_str(data).unwrap();
+
+        assert_eq!(table.players.len(), 2);
+        assert_eq!(config.num_players, 2);
+        assert_eq!(config.num_games, 3);
+        assert_eq!(config.num_cards_per_hand, 4);
+    }
+
+    #[test]
+    fn test_build_from_str_no_players() {
+        let data = r#"{"num_cards": 12, "num_games": 3, "players": []}"#;
+        // test
+        let result = build_from_str(data);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_build_from_str_too_few_cards() {
+        let data = r#"{"num_cards": 2, "num_games": 3,
+                       "players": [{"name": "bach", "strategy": "next_card"}, {"name": "liszt", "strategy": "max_card"}]}"#;
+        // test
+        let result = build_from_str(data);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_build_from_str_no_games() {
+        let data = r#"{"num_cards": 12, "num_games": 0,
+                       "players": [{"name": "bach", "strategy": "next_card"}, {"name": "liszt", "strategy": "max_card"}]}"#;
+        // test
+        let result = build_from_str(data);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_build_from_str_bad_external_command() {
+        let data = r#"{"num_cards": 12, "num_games": 3,
+                       "players": [{"name": "bot", "strategy": "external", "command": ["./no/such/bot"]},
+                                   {"name": "bach", "strategy": "next_card"}]}"#;
+        // test
+        let result = build_from_str(data);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_build_from_str_unknown_strategy() {
+        let data = r#"{"num_cards": 12, "num_games": 3,
+                       "players": [{"name": "bach", "strategy": "next_crad"}, {"name": "liszt", "strategy": "max_card"}]}"#;
+        // test
+        let result = build_from_str(data);
+
+        assert_eq!(result.err(), Some(String::from("bach has an unknown strategy \"next_crad\"")));
+    }
+
+    #[test]
+    fn test_build_from_str_duplicate_name() {
+        let data = r#"{"num_cards": 12, "num_games": 3,
+                       "players": [{"name": "bach", "strategy": "next_card"}, {"name": "bach", "strategy": "max_card"}]}"#;
+        // test
+        let result = build_from_str(data);
+
+        assert_eq!(result.err(), Some(String::from("player name \"bach\" appears more than once")));
+    }
+
+    #[test]
+    fn test_build_from_json_missing_file() {
+        // test
+        let result = build_from_json("no/such/config.json");
+
+        assert!(result.unwrap_err().starts_with("unable to read no/such/config.json"));
+    }
+
+    #[test]
+    fn test_build_from_str_duplicate_with_seating() {
+        let data = r#"{"num_cards": 12, "num_games": 3, "duplicate": "rotations", "seating": "rotate",
+                       "players": [{"name": "bach", "strategy": "next_card"}, {"name": "liszt", "strategy": "max_card"}]}"#;
+        // test
+        let result = build_from_str(data);
+
+        assert_eq!(result.err(), Some(String::from("duplicate and seating cannot be used together")));
+    }
+
+    #[test]
+    fn test_build_from_str_too_many_replays() {
+        let players: Vec<String> = (0..13).map(|i| format!(r#"{{"name": "p{}", "strategy": "next_card"}}"#, i)).collect();
+        let permutations = format!(r#"{{"num_cards": 28, "num_games": 1, "duplicate": "permutations", "players": [{}]}}"#, players.join(", "));
+        let rotations = format!(r#"{{"num_cards": 28, "num_games": 4000000000, "duplicate": "rotations", "players": [{}]}}"#, players.join(", "));
+
+        // test
+        assert_eq!(build_from_str(&permutations).err(), Some(String::from("too many games: 1 deals, each replayed for 13 players")));
+        assert!(build_from_str(&rotations).is_err());
+    }
+
+    #[test]
+    fn test_every_key_is_allowed_or_refused_over_http() {
+        let data = r#"{"num_cards": 12, "num_games": 3, "players": [{"name": "bach", "strategy": "next_card"}]}"#;
+        let json_configuration: JsonConfiguration = serde_json::from_str(data).unwrap();
+
+        // test
+        let value = serde_json::to_value(&json_configuration).unwrap();
+
+        for key in value.as_object().unwrap().keys() {
+            assert!(crate::http::ALLOWED_KEYS.contains(&key.as_str()) || crate::http::REFUSED_KEYS.contains(&key.as_str()),
+                    "{} must be allowed or refused over http", key);
+        }
+    }
 }
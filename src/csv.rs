@@ -0,0 +1,232 @@
+use std::borrow::Cow;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{BufWriter, Write};
+
+use crate::game::Table;
+
+// --csv-games and --csv-rounds: one line per player per game, and one line per round
+#[derive(Debug)]
+pub struct CsvExport {
+    games: Option<BufWriter<File>>,
+    rounds: Option<BufWriter<File>>,
+    // the bid columns of the rounds file, fixed when it is created even if players change seats
+    players: Vec<String>,
+}
+
+const GAMES_HEADER: &str = "game,seed,player,score,rounds_won,placement";
+
+// a resumed tourney appends to the files it wrote before, less any rows from games
+// after the checkpoint, which are about to be played again; also returns the header, if any
+fn open(file: &str, games_played: u32) -> io::Result<(BufWriter<File>, Option<String>)> {
+    if games_played == 0 {
+        return Ok((BufWriter::new(File::create(file)?), None));
+    }
+    let existing = match fs::read_to_string(file) {
+        Ok(existing) => existing,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    let mut header = None;
+    let mut kept = 0;
+    for line in existing.split_inclusive('\n') {
+        if header.is_none() {
+            header = Some(String::from(line.trim_end()));
+        } else {
+            // every row starts with its game, and the games are in order
+            let game = line.split(',').next().and_then(|game| game.parse::<u32>().ok());
+            if game.is_none_or(|game| game > games_played) {
+                break;
+            }
+        }
+        kept += line.len();
+    }
+    let file = OpenOptions::new().create(true).append(true).open(file)?;
+    file.set_len(kept as u64)?;
+    Ok((BufWriter::new(file), header))
+}
+
+// the fields of one line, unquoted
+fn fields(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+// the players of the bid columns, in the order of the header
+fn bid_columns(header: &str) -> Vec<String> {
+    fields(header).iter().filter_map(|column| column.strip_prefix("bid_")).map(String::from).collect()
+}
+
+// quoted only when needed, as in RFC 4180
+fn field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+impl CsvExport {
+    pub fn create(games_file: Option<&str>, rounds_file: Option<&str>, table: &Table) -> io::Result<CsvExport> {
+        let mut players: Vec<String> = table.players.iter().map(|p| String::from(p.name())).collect();
+
+        let games = match games_file {
+            Some(file) => {
+                let (mut writer, header) = open(file, table.games_played)?;
+                if header.is_none() {
+                    writeln!(writer, "{}", GAMES_HEADER)?;
+                }
+                Some(writer)
+            }
+            None => None,
+        };
+        let rounds = match rounds_file {
+            Some(file) => {
+                let (mut writer, header) = open(file, table.games_played)?;
+                match header {
+                    // the seats may have changed since, so the columns come from the file
+                    Some(header) => {
+                        let columns = bid_columns(&header);
+                        if columns.len() != players.len() || players.iter().any(|name| !columns.contains(name)) {
+                            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} has bids for other players", file)));
+                        }
+                        players = columns;
+                    }
+                    None => {
+                        let bids: Vec<String> = players.iter().map(|name| field(&format!("bid_{}", name)).into_owned()).collect();
+                        writeln!(writer, "game,round,prize_card,{},winner", bids.join(","))?;
+                    }
+                }
+                Some(writer)
+            }
+            None => None,
+        };
+        Ok(CsvExport{games, rounds, players})
+    }
+
+    // after update_game_winner, while the finished game is still on the table; game is from 1
+    pub fn write_game(&mut self, table: &Table, game: u32, seed: u64) -> io::Result<()> {
+        if let Some(writer) = &mut self.games {
+            for player in &table.players {
+                let stats = &player.player_stats;
+                writeln!(writer, "{},{},{},{},{},{}", game, seed, field(player.name()),
+                         stats.game_points.last().copied().unwrap_or(0),
+                         stats.game_rounds_won.last().copied().unwrap_or(0),
+                         stats.game_placements.last().copied().unwrap_or(0))?;
+            }
+            writer.flush()?;
+        }
+
+        if let (Some(writer), Some(state)) = (&mut self.rounds, &table.game) {
+            for (index, round) in state.rounds().iter().enumerate() {
+                let bids: Vec<String> = self.players.iter().map(|name| {
+                    match table.players.iter().position(|p| p.name() == name) {
+                        Some(seat) => round.bids[seat].to_string(),
+                        None => String::new(),
+                    }
+                }).collect();
+                let winner = round.winner.and_then(|seat| table.players.get(seat)).map(|p| p.name()).unwrap_or("");
+                writeln!(writer, "{},{},{},{},{}", game, index + 1, round.prize_card, bids.join(","), field(winner))?;
+            }
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::game;
+    use crate::player::Player;
+    use std::fs;
+
+    #[test]
+    fn test_field_quoting() {
+        assert_eq!(field("mozart"), "mozart");
+        assert_eq!(field("Mozart, W.A."), "\"Mozart, W.A.\"");
+        assert_eq!(field("\"Wolfie\""), "\"\"\"Wolfie\"\"\"");
+    }
+
+    #[test]
+    fn test_write_game_basic() {
+        let dir = std::env::temp_dir();
+        let games_file = dir.join(format!("waro_games_{}.csv", std::process::id()));
+        let rounds_file = dir.join(format!("waro_rounds_{}.csv", std::process::id()));
+        let players = vec![Player::new_from_json("mozart", "max_card"), Player::new_from_json("salieri", "min_card")];
+        let mut table = Table{players, seed: 11, .. Table::new()};
+        let config = Config::new(2, 1, 12);
+        table.csv = Some(CsvExport::create(Some(&games_file.to_string_lossy()), Some(&rounds_file.to_string_lossy()), &table).unwrap());
+
+        // test
+        game::run_tourney(&config, &mut table);
+        table.csv = None;
+
+        let games = fs::read_to_string(&games_file).unwrap();
+        let rounds = fs::read_to_string(&rounds_file).unwrap();
+        let _ = fs::remove_file(&games_file);
+        let _ = fs::remove_file(&rounds_file);
+
+        let games: Vec<&str> = games.lines().collect();
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[0], GAMES_HEADER);
+        assert!(games[1].starts_with("1,"), "{}", games[1]);
+        assert!(games[1].contains(",mozart,"), "{}", games[1]);
+
+        let rounds: Vec<&str> = rounds.lines().collect();
+        assert_eq!(rounds[0], "game,round,prize_card,bid_mozart,bid_salieri,winner");
+        // 12 cards over two hands and a kitty: 4 rounds
+        assert_eq!(rounds.len(), 5);
+        assert!(rounds[1].starts_with("1,1,"), "{}", rounds[1]);
+    }
+
+    #[test]
+    fn test_fields_quoted() {
+        // test
+        let result = fields("game,\"bid_Mozart, W.A.\",\"bid_\"\"Wolfie\"\"\",winner");
+
+        assert_eq!(result, vec!["game", "bid_Mozart, W.A.", "bid_\"Wolfie\"", "winner"]);
+    }
+
+    #[test]
+    fn test_create_on_resume() {
+        let dir = std::env::temp_dir();
+        let games_file = dir.join(format!("waro_resumed_games_{}.csv", std::process::id()));
+        let rounds_file = dir.join(format!("waro_resumed_rounds_{}.csv", std::process::id()));
+        fs::write(&games_file, format!("{}\n1,7,salieri,5,1,2\n1,7,mozart,10,2,1\n2,8,salieri,6,1,1\n", GAMES_HEADER)).unwrap();
+        fs::write(&rounds_file, "game,round,prize_card,bid_salieri,bid_mozart,winner\n1,1,5,3,4,mozart\n2,1,6,4,3,salieri\n").unwrap();
+        let players = vec![Player::new_from_json("mozart", "max_card"), Player::new_from_json("salieri", "min_card")];
+        let table = Table{players, games_played: 1, .. Table::new()};
+
+        // test
+        let result = CsvExport::create(Some(&games_file.to_string_lossy()), Some(&rounds_file.to_string_lossy()), &table).unwrap();
+        drop(result.games);
+        drop(result.rounds);
+
+        let games = fs::read_to_string(&games_file).unwrap();
+        let rounds = fs::read_to_string(&rounds_file).unwrap();
+        let _ = fs::remove_file(&games_file);
+        let _ = fs::remove_file(&rounds_file);
+
+        assert_eq!(result.players, vec!["salieri", "mozart"]);
+        assert_eq!(games, format!("{}\n1,7,salieri,5,1,2\n1,7,mozart,10,2,1\n", GAMES_HEADER));
+        assert_eq!(rounds, "game,round,prize_card,bid_salieri,bid_mozart,winner\n1,1,5,3,4,mozart\n");
+    }
+}
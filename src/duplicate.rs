@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::game::Table;
+
+// duplicate tourneys, as in duplicate bridge: each deal is replayed with the hands moved
+// around the table, so luck of the deal evens out and far fewer deals are needed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Duplicate {
+    // every player gets every hand once: one replay per seat
+    Rotations,
+    // every assignment of hands to players: n! replays
+    Permutations,
+}
+
+impl Duplicate {
+    // a config whose replays do not fit in a u32 is refused by build_from_str
+    pub fn num_replays(&self, num_players: usize) -> u32 {
+        self.checked_num_replays(num_players).expect("too many replays of each deal to count")
+    }
+
+    // None past 12 players for permutations
+    pub fn checked_num_replays(&self, num_players: usize) -> Option<u32> {
+        let num_players = u32::try_from(num_players).ok()?;
+        match self {
+            Duplicate::Rotations => Some(num_players),
+            Duplicate::Permutations => (1..=num_players).try_fold(1u32, |product, n| product.checked_mul(n)),
+        }
+    }
+
+    // which of the dealt hands each seat gets in the given replay of a deal
+    pub fn seating(&self, num_players: usize, replay: u32) -> Vec<usize> {
+        match self {
+            Duplicate::Rotations => (0..num_players).map(|seat| (seat + replay as usize) % num_players).collect(),
+            Duplicate::Permutations => nth_permutation(num_players, replay),
+        }
+    }
+}
+
+// in lexicographic order, from the factorial number system
+fn nth_permutation(n: usize, index: u32) -> Vec<usize> {
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut index = index as usize;
+    let mut permutation = vec![];
+    for position in (0..n).rev() {
+        let block: usize = (1..=position).product();
+        permutation.push(remaining.remove(index / block));
+        index %= block;
+    }
+    permutation
+}
+
+// one deal, summed over its replays
+#[derive(Debug, Clone, PartialEq)]
+pub struct DealResult {
+    pub deal: u32,
+    pub points: Vec<(String, u32)>,
+}
+
+impl DealResult {
+    pub fn winners(&self) -> Vec<&str> {
+        let best = self.points.iter().map(|(_, points)| *points).max().unwrap_or(0);
+        self.points.iter().filter(|(_, points)| *points == best).map(|(name, _)| name.as_str()).collect()
+    }
+}
+
+impl fmt::Display for DealResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let points: Vec<String> = self.points.iter().map(|(name, points)| format!("{} {}", name, points)).collect();
+        write!(f, "deal {}: {}", self.deal + 1, points.join(", "))
+    }
+}
+
+// the deals completed so far, from each player's points per game
+pub fn deal_results(table: &Table, num_replays: u32) -> Vec<DealResult> {
+    let num_replays = num_replays.max(1) as usize;
+    let num_deals = table.players.iter().map(|p| p.player_stats.game_points.len() / num_replays).min().unwrap_or(0);
+    (0..num_deals).map(|deal| {
+        let points = table.players.iter().map(|p| {
+            let replays = &p.player_stats.game_points[deal * num_replays..(deal + 1) * num_replays];
+            (String::from(p.name()), replays.iter().sum())
+        }).collect();
+        DealResult{deal: deal as u32, points}
+    }).collect()
+}
+
+#[derive(Debug)]
+pub struct Summary {
+    pub num_deals: u32,
+    // name, deals won (shared on a tie), mean points per deal
+    pub players: Vec<(String, f64, f64)>,
+}
+
+pub fn summarize(table: &Table, num_replays: u32) -> Summary {
+    let results = deal_results(table, num_replays);
+    let num_deals = results.len() as u32;
+    let players = table.players.iter().enumerate().map(|(seat, p)| {
+        let mut deals_won = 0.0;
+        let mut total = 0;
+        for result in &results {
+            let winners = result.winners();
+            if winners.contains(&p.name()) {
+                deals_won += 1.0 / winners.len() as f64;
+            }
+            total += result.points[seat].1;
+        }
+        let mean = if num_deals == 0 { 0.0 } else { total as f64 / num_deals as f64 };
+        (String::from(p.name()), deals_won, mean)
+    }).collect();
+    Summary{num_deals, players}
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "duplicate over {} deals:", self.num_deals)?;
+        for (name, deals_won, mean) in &self.players {
+            writeln!(f, "  {} won {:.1} deals, {:.1} points per deal", name, deals_won, mean)?;
+        }
+        Ok(())
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use crate::player::Player;
+
+    #[test]
+    fn test_seating_rotations() {
+        // test
+        let result: Vec<Vec<usize>> = (0..3).map(|r| Duplicate::Rotations.seating(3, r)).collect();
+
+        assert_eq!(result, vec![vec![0,1,2], vec![1,2,0], vec![2,0,1]]);
+    }
+
+    #[test]
+    fn test_seating_permutations() {
+        // test
+        let result: Vec<Vec<usize>> = (0..6).map(|r| Duplicate::Permutations.seating(3, r)).collect();
+
+        assert_eq!(Duplicate::Permutations.num_replays(3), 6);
+        assert_eq!(result, vec![vec![0,1,2], vec![0,2,1], vec![1,0,2], vec![1,2,0], vec![2,0,1], vec![2,1,0]]);
+    }
+
+    #[test]
+    fn test_checked_num_replays_overflow() {
+        // test
+        assert_eq!(Duplicate::Permutations.checked_num_replays(12), Some(479_001_600));
+        assert_eq!(Duplicate::Permutations.checked_num_replays(13), None);
+        assert_eq!(Duplicate::Rotations.checked_num_replays(13), Some(13));
+    }
+
+    #[test]
+    fn test_summarize_basic() {
+        let mut p1 = Player::new_from_json("mozart", "next_card");
+        let mut p2 = Player::new_from_json("salieri", "next_card");
+        p1.player_stats.game_points = vec![10, 20, 5, 5, 7];
+        p2.player_stats.game_points = vec![20, 5, 5, 5, 30];
+        let table = Table{players: vec![p1, p2], .. Table::new()};
+
+        // test
+        let result = summarize(&table, 2);
+
+        // the last game is a deal still in progress
+        assert_eq!(result.num_deals, 2);
+        assert_eq!(result.players[0], (String::from("mozart"), 1.5, 20.0));
+        assert_eq!(result.players[1], (String::from("salieri"), 0.5, 17.5));
+    }
+}
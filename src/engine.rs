@@ -0,0 +1,431 @@
+
+// the rules of the game with no I/O: no stdin, no printing and no
+// thread_rng, so it also builds for wasm32-unknown-unknown (see web/)
+
+use rand::seq::SliceRandom;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub mod builtin;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hand {
+    pub cards: Vec<u32>,
+}
+
+impl Hand {
+    pub fn new() -> Hand {
+        Hand {
+            cards: vec![],
+        }
+    }
+}
+
+impl Default for Hand {
+    fn default() -> Hand { Hand::new() }
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = String::new();
+
+        s.push('[');
+        let cards: Vec<String> = self.cards.iter().map(|card| card.to_string()).collect();
+        s.push_str(&cards.join(", "));
+        s.push(']');
+
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum IllegalBid {
+    NoCard,
+    NotInHand(u32),
+    // no prize is up, or this seat has already bid on it
+    OutOfTurn,
+}
+
+impl fmt::Display for IllegalBid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IllegalBid::NoCard => write!(f, "no card offered"),
+            IllegalBid::NotInHand(card) => write!(f, "card {} is not in hand", card),
+            IllegalBid::OutOfTurn => write!(f, "no bid is expected from this seat"),
+        }
+    }
+}
+
+// cards are numbered from 1, so a forfeited bid never wins a round
+pub const FORFEIT_OFFER: u32 = 0;
+
+// each game of a tourney gets its own seed, so a tourney can be resumed at any game
+pub fn game_seed(tourney_seed: u64, game_index: u32) -> u64 {
+    // splitmix64, so neighbouring games get unrelated seeds
+    let mut z = tourney_seed.wrapping_add(u64::from(game_index).wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+pub fn build_deck<R: Rng>(num_cards: u32, rng: &mut R) -> Vec<u32> {
+    let mut deck: Vec<u32> = (1..num_cards+1).collect();
+    deck.shuffle(rng);
+    deck
+}
+
+// returns the kitty and one hand per player; leftover cards are not dealt
+pub fn deal<R: Rng>(num_players: usize, num_cards: u32, rng: &mut R) -> (Hand, Vec<Hand>) {
+    let deck = build_deck(num_cards, rng);
+    let num_cards_per_hand = deck.len() / (num_players + 1);
+    let mut hands: Vec<Hand> = deck.chunks(num_cards_per_hand.max(1))
+                                   .take(num_players + 1)
+                                   .map(|cards| Hand{cards: cards.to_vec()})
+                                   .collect();
+    hands.resize(num_players + 1, Hand::new());
+    let kitty = hands.remove(0);
+    (kitty, hands)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Round {
+    pub prize_card: u32,
+    // one bid per seat
+    pub bids: Vec<u32>,
+    // nobody takes the prize when every seat forfeits
+    pub winner: Option<usize>,
+}
+
+// what a game is waiting for next
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Reveal,
+    Bidding,
+    Resolve,
+    Finished,
+}
+
+// one game, driven a step at a time: reveal a prize, collect a bid from every
+// seat, resolve the round, and repeat until the kitty is empty
+#[derive(Debug, Clone)]
+pub struct GameState {
+    pub num_cards: u32,
+    kitty: Hand,
+    hands: Vec<Hand>,
+    prize_card: Option<u32>,
+    bids: Vec<Option<u32>>,
+    scores: Vec<u32>,
+    rounds: Vec<Round>,
+    // a disqualified seat still bids, but cannot win the game
+    disqualified: Vec<bool>,
+}
+
+impl GameState {
+    // the same seed always deals the same game
+    pub fn new(num_players: usize, num_cards: u32, seed: u64) -> GameState {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let (kitty, hands) = deal(num_players, num_cards, &mut rng);
+        GameState::from_deal(kitty, hands, num_cards)
+    }
+
+    pub fn from_deal(kitty: Hand, hands: Vec<Hand>, num_cards: u32) -> GameState {
+        let num_players = hands.len();
+        GameState {
+            num_cards,
+            kitty,
+            hands,
+            prize_card: None,
+            bids: vec![None; num_players],
+            scores: vec![0; num_players],
+            rounds: vec![],
+            disqualified: vec![false; num_players],
+        }
+    }
+
+    pub fn num_players(&self) -> usize { self.hands.len() }
+    pub fn prize_card(&self) -> Option<u32> { self.prize_card }
+    pub fn hand(&self, seat: usize) -> &Hand { &self.hands[seat] }
+    pub fn kitty(&self) -> &Hand { &self.kitty }
+    pub fn scores(&self) -> &[u32] { &self.scores }
+    pub fn rounds(&self) -> &[Round] { &self.rounds }
+    pub fn is_disqualified(&self, seat: usize) -> bool { self.disqualified[seat] }
+
+    pub fn disqualify(&mut self, seat: usize) {
+        self.disqualified[seat] = true;
+    }
+
+    // turns over the next prize card; a prize already up stays up
+    pub fn reveal_prize(&mut self) -> Option<u32> {
+        if self.prize_card.is_none() {
+            self.prize_card = self.kitty.cards.pop();
+        }
+        self.prize_card
+    }
+
+    pub fn submit_bid(&mut self, seat: usize, card: u32) -> Result<(), IllegalBid> {
+        if self.prize_card.is_none() || self.bids.get(seat) != Some(&None) {
+            return Err(IllegalBid::OutOfTurn);
+        }
+        if !self.hands[seat].cards.contains(&card) {
+            return Err(IllegalBid::NotInHand(card));
+        }
+        self.bids[seat] = Some(card);
+        Ok(())
+    }
+
+    // gives up the lowest card in hand and bids nothing; how illegal bids and resignations are played
+    pub fn forfeit(&mut self, seat: usize) -> Result<(), IllegalBid> {
+        if self.prize_card.is_none() || self.bids.get(seat) != Some(&None) {
+            return Err(IllegalBid::OutOfTurn);
+        }
+        let cards = &mut self.hands[seat].cards;
+        if let Some(lowest) = cards.iter().min().copied() {
+            cards.retain(|card| *card != lowest);
+        }
+        self.bids[seat] = Some(FORFEIT_OFFER);
+        Ok(())
+    }
+
+    // the cards a seat may bid right now, lowest first
+    pub fn legal_bids(&self, seat: usize) -> Vec<u32> {
+        if !self.awaiting_bids().contains(&seat) {
+            return vec![];
+        }
+        let mut cards = self.hands[seat].cards.clone();
+        cards.sort_unstable();
+        cards
+    }
+
+    pub fn phase(&self) -> Phase {
+        match self.prize_card {
+            None if self.kitty.cards.is_empty() => Phase::Finished,
+            None => Phase::Reveal,
+            Some(_) if self.bids.iter().any(|bid| bid.is_none()) => Phase::Bidding,
+            Some(_) => Phase::Resolve,
+        }
+    }
+
+    // seats that still owe a bid on the current prize
+    pub fn awaiting_bids(&self) -> Vec<usize> {
+        if self.prize_card.is_none() {
+            return vec![];
+        }
+        (0..self.bids.len()).filter(|seat| self.bids[*seat].is_none()).collect()
+    }
+
+    // the highest bid takes the prize, the earlier seat on a tie, and a round of forfeits
+    // throws the prize away; returns None until every seat has bid
+    pub fn resolve_round(&mut self) -> Option<&Round> {
+        let prize_card = self.prize_card?;
+        let bids: Vec<u32> = self.bids.iter().copied().collect::<Option<Vec<u32>>>()?;
+
+        let best = bids.iter().enumerate().fold(0, |best, (seat, bid)| if *bid > bids[best] { seat } else { best });
+        let winner = if bids[best] == FORFEIT_OFFER { None } else { Some(best) };
+        for (hand, bid) in self.hands.iter_mut().zip(&bids) {
+            hand.cards.retain(|card| card != bid);
+        }
+        if let Some(winner) = winner {
+            self.scores[winner] += prize_card;
+        }
+        self.prize_card = None;
+        self.bids = vec![None; self.hands.len()];
+        self.rounds.push(Round{prize_card, bids, winner});
+        self.rounds.last()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.prize_card.is_none() && self.kitty.cards.is_empty()
+    }
+
+    // the most points wins, the earlier seat on a tie; a disqualified seat
+    // cannot win unless nobody else is left
+    pub fn winner(&self) -> Option<usize> {
+        if !self.is_finished() || self.scores.is_empty() {
+            return None;
+        }
+        let eligible = (0..self.scores.len()).filter(|seat| !self.disqualified[*seat]);
+        let winner = eligible.fold(None, |best: Option<usize>, seat| match best {
+            Some(best) if self.scores[seat] <= self.scores[best] => Some(best),
+            _ => Some(seat),
+        });
+        Some(winner.unwrap_or(0))
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    fn two_player_game() -> GameState {
+        let kitty = Hand{cards: vec![3, 9]};
+        let hands = vec![Hand{cards: vec![1, 8]}, Hand{cards: vec![2, 5]}];
+        GameState::from_deal(kitty, hands, 9)
+    }
+
+    #[test]
+    fn test_deal_basic() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        // test
+        let (kitty, hands) = deal(2, 12, &mut rng);
+
+        assert_eq!(kitty.cards.len(), 4);
+        assert_eq!(hands.len(), 2);
+        let mut cards: Vec<u32> = kitty.cards.iter().chain(hands.iter().flat_map(|h| &h.cards)).copied().collect();
+        cards.sort();
+        assert_eq!(cards, (1..13).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_new_same_seed_same_deal() {
+        // test
+        let a = GameState::new(3, 20, 42);
+        let b = GameState::new(3, 20, 42);
+
+        assert_eq!(a.kitty(), b.kitty());
+        assert_eq!(a.hand(2), b.hand(2));
+    }
+
+    #[test]
+    fn test_step_through_game() {
+        let mut game = two_player_game();
+
+        // test
+        assert_eq!(game.reveal_prize(), Some(9));
+        assert_eq!(game.awaiting_bids(), vec![0, 1]);
+        game.submit_bid(0, 8).unwrap();
+        assert_eq!(game.submit_bid(0, 1), Err(IllegalBid::OutOfTurn));
+        assert!(game.resolve_round().is_none());
+        game.submit_bid(1, 2).unwrap();
+        let round = game.resolve_round().unwrap().clone();
+        assert_eq!(round, Round{prize_card: 9, bids: vec![8, 2], winner: Some(0)});
+
+        assert_eq!(game.reveal_prize(), Some(3));
+        game.submit_bid(0, 1).unwrap();
+        game.submit_bid(1, 5).unwrap();
+        game.resolve_round();
+
+        assert!(game.is_finished());
+        assert_eq!(game.scores(), &[9, 3]);
+        assert_eq!(game.winner(), Some(0));
+    }
+
+    #[test]
+    fn test_phases_and_legal_bids() {
+        let mut game = two_player_game();
+        assert_eq!(game.phase(), Phase::Reveal);
+        assert_eq!(game.legal_bids(0), Vec::<u32>::new());
+
+        // test
+        game.reveal_prize();
+
+        assert_eq!(game.phase(), Phase::Bidding);
+        assert_eq!(game.legal_bids(1), vec![2, 5]);
+        game.submit_bid(1, 5).unwrap();
+        assert_eq!(game.legal_bids(1), Vec::<u32>::new());
+        game.submit_bid(0, 1).unwrap();
+        assert_eq!(game.phase(), Phase::Resolve);
+    }
+
+    #[test]
+    fn test_forfeit() {
+        let mut game = two_player_game();
+        game.reveal_prize();
+        game.submit_bid(1, 2).unwrap();
+
+        // test
+        game.forfeit(0).unwrap();
+
+        let round = game.resolve_round().unwrap().clone();
+        assert_eq!(round.bids, vec![FORFEIT_OFFER, 2]);
+        assert_eq!(round.winner, Some(1));
+        assert_eq!(game.hand(0).cards, vec![8]);
+    }
+
+    #[test]
+    fn test_resolve_round_all_forfeit() {
+        let mut game = two_player_game();
+        game.reveal_prize();
+        game.forfeit(0).unwrap();
+        game.forfeit(1).unwrap();
+
+        // test
+        let round = game.resolve_round().unwrap().clone();
+
+        assert_eq!(round.winner, None);
+        assert_eq!(game.scores(), &[0, 0]);
+    }
+
+    #[test]
+    fn test_winner_skips_disqualified() {
+        let mut game = two_player_game();
+        game.disqualify(0);
+        for bids in [[8, 2], [1, 5]] {
+            game.reveal_prize();
+            game.submit_bid(0, bids[0]).unwrap();
+            game.submit_bid(1, bids[1]).unwrap();
+            game.resolve_round();
+        }
+
+        // test
+        let result = game.winner();
+
+        assert_eq!(game.scores(), &[9, 3]);
+        assert_eq!(result, Some(1));
+    }
+
+    #[test]
+    fn test_resolve_round_highest_bid_wins() {
+        let kitty = Hand{cards: vec![18]};
+        let hands = vec![Hand{cards: vec![10]}, Hand{cards: vec![14]}, Hand{cards: vec![7]}];
+        let mut game = GameState::from_deal(kitty, hands, 18);
+        game.reveal_prize();
+        game.submit_bid(0, 10).unwrap();
+        game.submit_bid(1, 14).unwrap();
+        game.submit_bid(2, 7).unwrap();
+
+        // test
+        let result = game.resolve_round().unwrap();
+
+        assert_eq!(result.winner, Some(1));
+    }
+
+    #[test]
+    fn test_submit_bid_not_in_hand() {
+        let mut game = two_player_game();
+        game.reveal_prize();
+
+        // test
+        let result = game.submit_bid(1, 8);
+
+        assert_eq!(result, Err(IllegalBid::NotInHand(8)));
+    }
+
+    #[test]
+    fn test_submit_bid_before_reveal() {
+        let mut game = two_player_game();
+
+        // test
+        let result = game.submit_bid(0, 8);
+
+        assert_eq!(result, Err(IllegalBid::OutOfTurn));
+    }
+
+    #[test]
+    fn test_resolve_round_tie_goes_to_earlier_seat() {
+        let kitty = Hand{cards: vec![4]};
+        let hands = vec![Hand{cards: vec![6]}, Hand{cards: vec![6]}];
+        let mut game = GameState::from_deal(kitty, hands, 6);
+        game.reveal_prize();
+        game.submit_bid(0, 6).unwrap();
+        game.submit_bid(1, 6).unwrap();
+
+        // test
+        let result = game.resolve_round().unwrap();
+
+        assert_eq!(result.winner, Some(0));
+    }
+}
@@ -1,58 +1,38 @@
 
-use std::io;
-
-use super::super::Hand;
-
-// this should be a trait !!??
-#[derive(Debug)]
-pub struct Strategy {
-    pub name: String,
-}
-
-const CONSOLE: &str = "console";
-const HYBRID_CARD: &str = "hybrid_card";
-const MAX_CARD: &str = "max_card";
-const MIN_CARD: &str = "min_card";
-const NEAREST_CARD: &str = "nearest_card";
-const NEXT_CARD: &str = "next_card";
-
-impl Strategy {
-    pub fn select_card(&self, prize_card: u32, hand: &Hand, highest_card: u32) -> u32 {
-
-        match self.name.as_str() {
-            CONSOLE => console(prize_card, hand, highest_card),
-            HYBRID_CARD => hybrid_card(prize_card, hand, highest_card),
-            MAX_CARD => max_card(prize_card, hand, highest_card),
-            MIN_CARD => min_card(prize_card, hand, highest_card),
-            NEAREST_CARD => nearest_card(prize_card, hand, highest_card),
-            NEXT_CARD => next_card(prize_card, hand, highest_card),
-            _ => next_card(prize_card, hand, highest_card),
-        }
-    }
-}
-
-fn console(prize_card: u32, hand: &Hand, _highest_card: u32) -> u32 {
-    let mut selection = None;
-    let mut pick = String::new();
-    println!("\nprize_card: {}", prize_card);
-    println!("your hand: {}", hand);
-
-    while ! selection.is_some() {
-        println!("enter your pick:");
-        selection = match io::stdin().read_line(&mut pick) {
-            Ok(_) => validate_pick(&pick, hand),
-            Err(_) => None,
-        }
-    }
-
-    selection.unwrap()
+// the strategies that need nothing but the prize and the hand
+
+use super::Hand;
+
+pub const HYBRID_CARD: &str = "hybrid_card";
+pub const MAX_CARD: &str = "max_card";
+pub const MIN_CARD: &str = "min_card";
+pub const NEAREST_CARD: &str = "nearest_card";
+pub const NEXT_CARD: &str = "next_card";
+
+pub const NAMES: [&str; 5] = [HYBRID_CARD, MAX_CARD, MIN_CARD, NEAREST_CARD, NEXT_CARD];
+
+// None if there is no built-in strategy of that name
+pub fn built_in_card(name: &str, prize_card: u32, hand: &Hand, highest_card: u32) -> Option<u32> {
+    let card = match name {
+        HYBRID_CARD => hybrid_card(prize_card, hand, highest_card),
+        MAX_CARD => max_card(prize_card, hand, highest_card),
+        MIN_CARD => min_card(prize_card, hand, highest_card),
+        NEAREST_CARD => nearest_card(prize_card, hand, highest_card),
+        NEXT_CARD => next_card(prize_card, hand, highest_card),
+        _ => return None,
+    };
+    Some(card)
 }
 
-fn validate_pick(pick: &str, hand: &Hand) -> Option<u32>  {
-    let guess: u32 = pick.trim().parse().expect("Please type a number!");
-    match hand.cards.contains(&guess) {
-        false => None,
-        true => Some(guess),
+// a one-line reason for the card a built-in strategy picks, used by the coach
+pub fn explain(name: &str, prize_card: u32, highest_card: u32) -> String {
+    match name {
+        HYBRID_CARD if prize_card > (highest_card / 2) => format!("the prize is above {}, so bid high", highest_card / 2),
+        HYBRID_CARD => format!("the prize is {} or below, so bid low", highest_card / 2),
+        MAX_CARD => String::from("always bid the highest card"),
+        MIN_CARD => String::from("always bid the lowest card"),
+        NEAREST_CARD => format!("bid the card closest to the prize of {}", prize_card),
+        _ => String::from("bid the first card in hand"),
     }
 }
 
@@ -67,7 +47,7 @@ fn hybrid_card(prize_card: u32, hand: &Hand, highest_card: u32) -> u32 {
 fn nearest_card(prize_card: u32, hand: &Hand, highest_card: u32) -> u32 {
     let (nearest, _tmp) = hand.cards.iter().fold((0, highest_card), |acc, card| -> (u32, u32) {
         let (_nearest_card_so_far, nearest_distance_so_far) = acc;
-        let this_distance = (*card as i32 - prize_card as i32).abs() as u32;
+        let this_distance = (*card as i32 - prize_card as i32).unsigned_abs();
         if this_distance < nearest_distance_so_far  {
             (*card, this_distance)
         } else {
@@ -92,49 +72,6 @@ fn min_card(_prize_card: u32, hand: &Hand, _highest_card: u32) -> u32 {
 #[allow(unused_imports)]
 mod tests {
     use super::*;
-    use std::panic;
-
-    #[test]
-    fn test_validate_pick_ok() {
-        let pick = "8";
-        let hand = Hand{cards: vec![4,2,6,8]};
-        // test
-        let result = validate_pick(&pick, &hand);
-
-        assert_eq!(result.unwrap(), 8);
-    }
-
-    #[test]
-    fn test_validate_pick_ok_newline() {
-        let pick = "8\n";
-        let hand = Hand{cards: vec![4,2,6,8]};
-        // test
-        let result = validate_pick(&pick, &hand);
-
-        assert_eq!(result.unwrap(), 8);
-    }
-
-    #[test]
-    fn test_validate_pick_not_legal() {
-        let pick = "@2";
-        let hand = Hand{cards: vec![4,2,6,8]};
-        // test
-        let result = panic::catch_unwind(|| {
-            validate_pick(&pick, &hand);
-        });
-
-        assert!(result.is_err());
-    }
-
-    #[test]
-    fn test_validate_pick_not_found() {
-        let pick = "7";
-        let hand = Hand{cards: vec![4,2,6,8]};
-        // test
-        let result = validate_pick(&pick, &hand);
-
-        assert_eq!(false, result.is_some());
-    }
 
     #[test]
     fn test_hybrid_card_high() {
@@ -223,4 +160,13 @@ mod tests {
 
         assert_eq!(result, 4);
     }
+
+    #[test]
+    fn test_built_in_card_unknown_name() {
+        let hand = Hand{cards: vec![4,2,6,8]};
+        // test
+        let result = built_in_card("next_crad", 10, &hand, 12);
+
+        assert_eq!(result, None);
+    }
 }
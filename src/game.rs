@@ -0,0 +1,941 @@
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Fn;
+
+use crate::analyze::Analysis;
+use crate::checkpoint;
+use crate::checkpoint::Checkpointing;
+use crate::config::Config;
+use crate::csv::CsvExport;
+use crate::duplicate;
+use crate::duplicate::Duplicate;
+use crate::engine;
+use crate::interrupt;
+use crate::engine::{GameState, Hand, IllegalBid};
+use crate::html::HtmlReport;
+use crate::player::{Penalty, Player};
+use crate::ratings::Ratings;
+use crate::report;
+use crate::spectator::{Event, Feed, Seat};
+use crate::strategy::console::ScoreLine;
+use crate::strategy::external::{Offer, Score};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Table {
+    pub players: Vec<Player>,
+    pub penalty: Penalty,
+    // several humans share one terminal, so nobody may see another's hand
+    pub hot_seat: bool,
+    // streams the tourney to websocket spectators
+    #[serde(skip)]
+    pub feed: Option<Feed>,
+    // the game in progress, dealt by deal_to_table; it alone holds the kitty, the hands and the bids
+    #[serde(skip)]
+    pub game: Option<GameState>,
+    // every deal in the tourney follows from this, see engine::game_seed
+    pub seed: u64,
+    pub games_played: u32,
+    pub checkpoint: Option<Checkpointing>,
+    // updated after every game and written back to the ratings file at the end
+    #[serde(default)]
+    pub ratings: Option<Ratings>,
+    // each deal is replayed with the hands moved around the table
+    #[serde(default)]
+    pub duplicate: Option<Duplicate>,
+    // players change seats between games, to measure and even out positional bias
+    #[serde(default)]
+    pub seating: Option<Seating>,
+    #[serde(skip)]
+    pub csv: Option<CsvExport>,
+    #[serde(skip)]
+    pub html: Option<HtmlReport>,
+    // profiles every bid, for `cargo run analyze`
+    #[serde(skip)]
+    pub analysis: Option<Analysis>,
+    // no TRACER lines for this table's games, see run_quiet_tourney
+    #[serde(skip)]
+    pub quiet: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Seating {
+    // everyone moves one seat towards the first
+    Rotate,
+    Shuffle,
+}
+
+impl Table {
+    pub fn new() -> Table {
+        Table {
+            players: vec![],
+            penalty: Penalty::default(),
+            hot_seat: false,
+            feed: None,
+            game: None,
+            seed: rand::random(),
+            games_played: 0,
+            checkpoint: None,
+            ratings: None,
+            duplicate: None,
+            seating: None,
+            csv: None,
+            html: None,
+            analysis: None,
+            quiet: false,
+        }
+    }
+}
+
+impl Default for Table {
+    fn default() -> Table { Table::new() }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut s = String::new();
+
+        if let Some(game) = &self.game {
+            s.push_str(&format!("\n\nkitty: {}\n", game.kitty()));
+            s.push_str(&format!("prize_card: {}\n", shown_prize(game)));
+        }
+        for seat in 0..self.players.len() {
+            s.push_str(&format!("{}\n", describe(self, seat)));
+        }
+
+        write!(f, "{}", s)
+    }
+}
+
+fn publish(table: &Table, event: &Event) {
+    if let Some(feed) = &table.feed {
+        feed.publish(event);
+    }
+}
+
+// the prize that is up, or else the last one played
+fn shown_prize(game: &GameState) -> u32 {
+    game.prize_card().or_else(|| game.rounds().last().map(|round| round.prize_card)).unwrap_or(0)
+}
+
+// a player, with the hand the game holds for its seat
+fn describe(table: &Table, seat: usize) -> String {
+    let player = &table.players[seat];
+    match &table.game {
+        Some(game) if seat < game.num_players() => format!("{} {} {}", player.name, game.hand(seat), player.player_stats),
+        _ => player.to_string(),
+    }
+}
+
+fn publish_table(table: &Table) {
+    if let (Some(feed), Some(game)) = (&table.feed, &table.game) {
+        let players = table.players.iter().enumerate().map(|(seat, p)| Seat{
+            name: &p.name,
+            hand: if feed.hide_hands { None } else { Some(&game.hand(seat).cards) },
+            total_for_game: p.player_stats.total_for_game,
+            num_rounds_won: p.player_stats.num_rounds_won,
+            num_games_won: p.player_stats.num_games_won,
+            disqualified: p.player_stats.disqualified,
+        }).collect();
+        feed.publish(&Event::Table{prize_card: shown_prize(game), kitty_size: game.kitty().cards.len(), players});
+    }
+}
+
+fn scores_for_game(players: &[Player]) -> Vec<Score> {
+    players.iter().map(|p| Score{name: p.name.clone(), total: p.player_stats.total_for_game}).collect()
+}
+
+// starts a new game on the table, replacing any game before it
+// the deal depends only on the tourney seed and how many games have been played
+// in a duplicate tourney, the same deal comes around again until every replay of it is played
+pub fn deal_to_table(config: &Config, table: &mut Table) {
+    let num_players = table.players.len();
+    let seating = match table.duplicate {
+        Some(duplicate) => duplicate.seating(num_players, table.games_played % duplicate.num_replays(num_players)),
+        None => (0..num_players).collect(),
+    };
+    let mut rng = StdRng::seed_from_u64(deal_seed(table));
+    let (kitty, hands) = engine::deal(num_players, config.num_cards, &mut rng);
+    let hands = seating.iter().map(|hand_index| hands[*hand_index].clone()).collect();
+    table.game = Some(GameState::from_deal(kitty, hands, config.num_cards));
+}
+
+// the first game keeps the seats from the config; a shuffle has its own stream, apart from the deals
+fn change_seats(table: &mut Table) {
+    if table.games_played == 0 {
+        return;
+    }
+    match table.seating {
+        Some(Seating::Rotate) => table.players.rotate_left(1),
+        Some(Seating::Shuffle) => {
+            let mut rng = StdRng::seed_from_u64(engine::game_seed(!table.seed, table.games_played));
+            table.players.shuffle(&mut rng);
+        }
+        None => {}
+    }
+}
+
+// the seed of the deck for the game about to be played
+pub fn deal_seed(table: &Table) -> u64 {
+    let deal_index = match table.duplicate {
+        Some(duplicate) => table.games_played / duplicate.num_replays(table.players.len()),
+        None => table.games_played,
+    };
+    engine::game_seed(table.seed, deal_index)
+}
+
+// games in the whole tourney; a duplicate tourney plays num_games deals
+pub fn tourney_length(config: &Config, table: &Table) -> u32 {
+    match table.duplicate {
+        Some(duplicate) => config.num_games.checked_mul(duplicate.num_replays(table.players.len()))
+                                           .expect("too many games in the tourney to count"),
+        None => config.num_games,
+    }
+}
+
+// every seat sees the prize before anyone is asked, so external bots can think at the same time
+fn get_bids(game: &mut GameState, penalty: Penalty, players: &mut [Player], quiet: bool) -> Result<(), IllegalBid> {
+    let prize_card = game.prize_card().ok_or(IllegalBid::OutOfTurn)?;
+    for (seat, player) in players.iter_mut().enumerate() {
+        if !game.is_disqualified(seat) {
+            player.reveal_prize(prize_card, game.hand(seat), game.num_cards);
+        }
+    }
+    for (seat, player) in players.iter_mut().enumerate() {
+        take_bid(game, seat, player, prize_card, penalty, quiet)?;
+    }
+    Ok(())
+}
+
+// submits one seat's bid to the game; a bid the game refuses is played as the penalty
+fn take_bid(game: &mut GameState, seat: usize, player: &mut Player, prize_card: u32, penalty: Penalty, quiet: bool) -> Result<(), IllegalBid> {
+    if game.is_disqualified(seat) || player.has_resigned() {
+        return game.forfeit(seat);
+    }
+    let selection = player.get_bid(prize_card, game.hand(seat), game.num_cards);
+    if player.has_resigned() {
+        if !quiet {
+            tracer!("TRACER {} resigns", player.name);
+        }
+        return game.forfeit(seat);
+    }
+
+    let result = match selection {
+        Some(card) => game.submit_bid(seat, card),
+        None => Err(IllegalBid::NoCard),
+    };
+    let e = match result {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+    if !quiet {
+        tracer!("TRACER {} makes an illegal bid: {}", player.name, e);
+    }
+    player.bids_illegally();
+    match penalty {
+        Penalty::ForfeitRound => game.forfeit(seat),
+        Penalty::PlayLowest => match game.legal_bids(seat).first() {
+            Some(lowest) => game.submit_bid(seat, *lowest),
+            None => game.forfeit(seat),
+        },
+        Penalty::Disqualify => {
+            game.disqualify(seat);
+            player.disqualify();
+            game.forfeit(seat)
+        }
+    }
+}
+
+// humans see the scores the table keeps, not ones worked out from what they were told
+fn update_scoreboards(table: &mut Table) {
+    if !table.players.iter().any(|p| p.is_human()) {
+        return;
+    }
+    let scoreboard: Vec<ScoreLine> = table.players.iter().map(|p| ScoreLine{
+        name: p.name.clone(),
+        points: p.player_stats.total_for_game,
+        rounds_won: p.player_stats.num_rounds_won,
+        games_won: p.player_stats.num_games_won,
+    }).collect();
+    for player in table.players.iter_mut().filter(|p| p.is_human()) {
+        player.show_scoreboard(&scoreboard);
+    }
+}
+
+fn update_round_winner(table: &mut Table, prize_card: u32, round_winner_name: String) {
+    for player in &mut table.players {
+        if player.name == round_winner_name {
+            table_tracer!(table, "TRACER {} WINS round: ", round_winner_name);
+            player.wins_round(prize_card);
+        }
+    }
+    if !table.hot_seat {
+        for seat in 0..table.players.len() {
+            table_tracer!(table, "TRACER {}", describe(table, seat));
+        }
+    }
+}
+
+fn play_round(table: &mut Table) -> Result<(u32, String), String> {
+    let game = table.game.as_mut().ok_or_else(|| String::from("no game in progress"))?;
+    let prize_card = game.reveal_prize().ok_or_else(|| String::from("no prize card left"))?;
+    table_tracer!(table, "\nTRACER play_round prize_card: {}", prize_card);
+    if let Some(feed) = &table.feed {
+        feed.publish(&Event::PrizeRevealed{prize_card});
+    }
+    let hands: Vec<Hand> = (0..game.num_players()).map(|seat| game.hand(seat).clone()).collect();
+    get_bids(game, table.penalty, &mut table.players, table.quiet).map_err(|e| e.to_string())?;
+    let round = game.resolve_round().ok_or_else(|| String::from("a seat has not bid"))?.clone();
+
+    let offers: Vec<Offer> = table.players.iter().zip(&round.bids).map(|(p, bid)| Offer{name: p.name.clone(), offer: *bid}).collect();
+    if table.hot_seat {
+        // every bid is revealed at once, without the hands behind them
+        println!("all bids are in for prize card {}:", prize_card);
+        for offer in &offers {
+            println!("  {}: {}", offer.name, offer.offer);
+        }
+    } else {
+        for (offer, hand) in offers.iter().zip(&hands) {
+            table_tracer!(table, "TRACER player: {:?} bids {:?} on {:?} with hand: {}", offer.name, offer.offer, prize_card, hand);
+        }
+    }
+
+    // nobody is named when every seat forfeits
+    let winner_name = round.winner.map(|seat| offers[seat].name.clone()).unwrap_or_default();
+    if let Some(feed) = &table.feed {
+        feed.publish(&Event::Bids{prize_card, bids: &offers});
+    }
+    for player in &mut table.players {
+        player.end_round(prize_card, &offers, &winner_name);
+    }
+
+    Ok((prize_card, winner_name))
+}
+
+// plays one round of the game dealt to the table and credits the winner; returns the prize card
+// and the winner, or an error when no game was dealt or its last prize is gone
+pub fn play_next_round(table: &mut Table) -> Result<(u32, String), String> {
+    let (prize_card, round_winner_name) = play_round(table)?;
+    update_round_winner(table, prize_card, round_winner_name.clone());
+    update_scoreboards(table);
+    publish(table, &Event::RoundResult{prize_card, winner: &round_winner_name, scores: &scores_for_game(&table.players)});
+    publish_table(table);
+    Ok((prize_card, round_winner_name))
+}
+
+fn update_game_winner(table: &mut Table, game_winner_name: String) {
+    let placements = rank_game(&table.players);
+    for (seat, player) in table.players.iter_mut().enumerate() {
+        if let Some(placement) = placements.iter().find(|p| p.name == player.name) {
+            player.finishes_game(seat as u32, placement.place);
+        }
+        if player.name == game_winner_name {
+            table_tracer!(table, "TRACER {} WINS game: ", game_winner_name);
+            player.wins_game();
+        } else {
+            player.loses_game();
+        }
+    }
+    for seat in 0..table.players.len() {
+        table_tracer!(table, "TRACER {}", describe(table, seat));
+    }
+}
+
+// with use_dealer false, the game already dealt to the table is played
+pub fn play_game(config: &Config, table: &mut Table, use_dealer: bool) -> String {
+    if use_dealer {
+        deal_to_table(config, table);
+    }
+    let game = match &table.game {
+        Some(game) => game,
+        None => return String::new(),
+    };
+
+    if !table.hot_seat {
+        table_tracer!(table, "TRACER play_game kitty: {}", game.kitty());
+        for seat in 0..table.players.len() {
+            table_tracer!(table, "TRACER play_game {}", describe(table, seat));
+        }
+    }
+    for (seat, p) in table.players.iter_mut().enumerate() {
+        p.begin_game(config.num_players, config.num_cards, game.hand(seat));
+    }
+    update_scoreboards(table);
+    publish(table, &Event::GameStart{num_players: config.num_players, num_cards: config.num_cards});
+    publish_table(table);
+    while table.game.as_ref().is_some_and(|game| !game.is_finished()) {
+        if let Err(e) = play_next_round(table) {
+            table_tracer!(table, "TRACER {}", e);
+            break;
+        }
+    }
+
+    // the engine decides, so a disqualified player cannot win
+    let winner = table.game.as_ref().and_then(GameState::winner);
+    let game_winner_name = winner.map(|seat| table.players[seat].name.clone()).unwrap_or_default();
+    let scores = scores_for_game(&table.players);
+    publish(table, &Event::GameOver{winner: &game_winner_name, scores: &scores});
+
+    for player in &mut table.players {
+        player.end_game(&game_winner_name, &scores);
+    }
+
+    game_winner_name
+}
+
+fn determine_tourney_winner(players: &[Player]) -> &Player {
+    determine_winner(players, tourney_selector)
+}
+
+// disqualified players cannot win, unless nobody is left
+fn determine_winner<'a,P>(players: &'a [Player], selector: P) -> &'a Player
+    where P: Fn(&'a Player) -> u32 {
+    let eligible = players.iter().filter(|p| !p.is_disqualified());
+    let winner = eligible.fold(None, |max, player| match max {
+        None => Some(player),
+        Some(y) => Some(if selector(player) > selector(y) { player } else { y }),
+    });
+    winner.unwrap_or(&players[0])
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    pub name: String,
+    pub score: u32,
+    // 1 for first; tied players share a place and the next place is skipped
+    pub place: u32,
+}
+
+// every player by points in the game just played, best first
+pub fn rank_game(players: &[Player]) -> Vec<Placement> {
+    rank(players, game_selector)
+}
+
+// every player by games won so far
+pub fn rank_tourney(players: &[Player]) -> Vec<Placement> {
+    rank(players, tourney_selector)
+}
+
+// disqualified players place behind everyone else, as they cannot win
+fn rank<P>(players: &[Player], selector: P) -> Vec<Placement>
+    where P: Fn(&Player) -> u32 {
+    let key = |player: &Player| (player.is_disqualified(), std::cmp::Reverse(selector(player)));
+    let mut placements: Vec<Placement> = players.iter().map(|player| {
+        let ahead = players.iter().filter(|other| key(other) < key(player)).count();
+        Placement{name: player.name.clone(), score: selector(player), place: 1 + ahead as u32}
+    }).collect();
+    placements.sort_by_key(|p| p.place);
+    placements
+}
+
+fn tourney_selector(player: &Player) -> u32 {
+    player.player_stats.num_games_won
+}
+
+fn game_selector(player: &Player) -> u32 {
+    player.player_stats.total_for_game
+}
+
+// deals and plays the next game of the tourney, then saves a checkpoint if one is due
+pub fn play_next_game(config: &Config, table: &mut Table) -> String {
+    change_seats(table);
+    let use_dealer = true;
+    let game_winner_name = play_game(config, table, use_dealer);
+    table_tracer!(table, "\n\nTRACER game {}", game_winner_name);
+    update_game_winner(table, game_winner_name.clone());
+    if let Some(ratings) = &mut table.ratings {
+        ratings.rate_game(&table.players);
+    }
+    if let Some(mut csv) = table.csv.take() {
+        if let Err(e) = csv.write_game(table, table.games_played + 1, deal_seed(table)) {
+            tracer!("TRACER could not write csv: {}", e);
+        }
+        table.csv = Some(csv);
+    }
+    if let Some(mut html) = table.html.take() {
+        html.record_game(table, table.games_played + 1);
+        table.html = Some(html);
+    }
+    if let (Some(analysis), Some(state)) = (&mut table.analysis, &table.game) {
+        let strategies: Vec<&str> = table.players.iter().map(|p| p.strategy_name()).collect();
+        analysis.record_game(&strategies, state);
+    }
+    table.games_played += 1;
+    if let Some(duplicate) = table.duplicate {
+        let num_replays = duplicate.num_replays(table.players.len());
+        if table.games_played.is_multiple_of(num_replays) {
+            if let Some(result) = duplicate::deal_results(table, num_replays).last() {
+                table_tracer!(table, "TRACER duplicate {}", result);
+            }
+        }
+    }
+    checkpoint::save_if_due(config, table);
+    game_winner_name
+}
+
+fn save_ratings(table: &Table) {
+    if let Some(ratings) = &table.ratings {
+        if let Err(e) = ratings.save() {
+            tracer!("TRACER could not save ratings to {}: {}", ratings.config.file, e);
+        }
+    }
+}
+
+pub fn play_tourney(config: &Config, table: &mut Table) {
+    run_tourney(config, table);
+    if let Some(ratings) = &table.ratings {
+        print!("leaderboard:\n{}", ratings.leaderboard());
+    }
+
+    for player in &mut table.players {
+        player.leave_table();
+    }
+    if let Some(feed) = table.feed.take() {
+        feed.finish();
+    }
+}
+
+// games played, won and the win rate of each player so far
+pub fn standings(table: &Table) -> String {
+    let mut s = String::new();
+    for player in &table.players {
+        let won = player.player_stats.num_games_won;
+        let win_rate = if table.games_played == 0 { 0.0 } else { 100.0 * won as f64 / table.games_played as f64 };
+        s.push_str(&format!("{}: played {} won {} ({:.1}%)\n", player.name, table.games_played, won, win_rate));
+    }
+    s
+}
+
+// plays every game but leaves bots connected, so an arena can seat them again
+pub fn run_tourney(config: &Config, table: &mut Table) -> String {
+    let num_games = tourney_length(config, table);
+    while table.games_played < num_games && !interrupt::requested() {
+        play_next_game(config, table);
+    }
+
+    if table.games_played < num_games {
+        println!("\n\ntourney interrupted after {} of {} games", table.games_played, num_games);
+        print!("{}", standings(table));
+        if !checkpoint::save_now(config, table) {
+            println!("add \"checkpoint\" to the config to be able to resume");
+        }
+        save_ratings(table);
+        return determine_tourney_winner(&table.players).name.clone();
+    }
+
+    let tourney_winner_name = determine_tourney_winner(&table.players).name.clone();
+    println!("\n\ntourney complete. WINNER: {}", tourney_winner_name);
+    for placement in rank_tourney(&table.players) {
+        println!("  {}. {} won {}", placement.place, placement.name, placement.score);
+    }
+    println!("final table: {}", table);
+    println!("{}", report::build(table));
+    if let Some(duplicate) = table.duplicate {
+        println!("{}", duplicate::summarize(table, duplicate.num_replays(table.players.len())));
+    }
+    save_ratings(table);
+    end_tourney(table, &tourney_winner_name);
+
+    tourney_winner_name
+}
+
+// as run_tourney, but prints nothing and plays every game whatever Ctrl-C says, for tables
+// that are part of something bigger: a tournament, a matrix or a simulation over http
+pub fn run_quiet_tourney(config: &Config, table: &mut Table) -> String {
+    table.quiet = true;
+    let num_games = tourney_length(config, table);
+    while table.games_played < num_games {
+        play_next_game(config, table);
+    }
+    save_ratings(table);
+
+    let tourney_winner_name = determine_tourney_winner(&table.players).name.clone();
+    end_tourney(table, &tourney_winner_name);
+    tourney_winner_name
+}
+
+fn end_tourney(table: &mut Table, tourney_winner_name: &str) {
+    let scores: Vec<Score> = table.players.iter().map(|p| Score{name: p.name.clone(), total: p.player_stats.num_games_won}).collect();
+    publish(table, &Event::TourneyOver{winner: tourney_winner_name, scores: &scores});
+    publish_table(table);
+    for player in &mut table.players {
+        player.end_tourney(tourney_winner_name, &scores);
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+    use crate::engine::FORFEIT_OFFER;
+    use crate::strategy::{Bidder, Strategy};
+
+    // some of these tests are enormous, but make me feel more comfortable with the new language
+
+    // a table of players with the given names and a game dealt to them
+    #[allow(dead_code)]
+    fn dealt_table(names: &[&str], hands: Vec<Vec<u32>>, kitty: Vec<u32>, num_cards: u32) -> Table {
+        let players = names.iter().map(|name| Player{name: String::from(*name), .. Player::new()}).collect();
+        let hands = hands.into_iter().map(|cards| Hand{cards}).collect();
+        let game = GameState::from_deal(Hand{cards: kitty}, hands, num_cards);
+        Table{players, game: Some(game), .. Table::new()}
+    }
+
+    // always bids a 7, whatever is in hand
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct Cheater;
+
+    impl Bidder for Cheater {
+        fn select_card(&mut self, _prize_card: u32, _hand: &Hand, _highest_card: u32) -> Option<u32> {
+            Some(7)
+        }
+    }
+
+    #[allow(dead_code)]
+    fn cheating_table(penalty: Penalty) -> Table {
+        let mut table = dealt_table(&["cheater", "mozart"], vec![vec![4,2,6,8], vec![1,3,5,9]], vec![10,11,12], 12);
+        table.players[0].strategy = Strategy::new_custom("cheater", Box::new(Cheater));
+        table.penalty = penalty;
+        table
+    }
+
+    #[test]
+    fn test_play_round_last_round() {
+        let mut table = dealt_table(&["mozart", "beethoven", "liszt"], vec![vec![10], vec![14], vec![7]], vec![18], 18);
+
+        // test
+        let result = play_round(&mut table);
+
+        assert_eq!(result, Ok((18, String::from("beethoven"))));
+        let game = table.game.unwrap();
+        assert!(game.is_finished());
+        assert!((0..3).all(|seat| game.hand(seat).cards.is_empty()));
+    }
+
+    #[test]
+    fn test_illegal_bid_forfeits_round() {
+        let mut table = cheating_table(Penalty::ForfeitRound);
+
+        // test
+        let result = play_round(&mut table);
+
+        assert_eq!(result, Ok((12, String::from("mozart"))));
+        let game = table.game.as_ref().unwrap();
+        assert_eq!(game.rounds()[0].bids, vec![FORFEIT_OFFER, 1]);
+        assert_eq!(game.hand(0).cards, vec![4,6,8]);
+        assert_eq!(table.players[0].player_stats.num_illegal_bids, 1);
+        assert!(!table.players[0].is_disqualified());
+    }
+
+    #[test]
+    fn test_illegal_bid_plays_lowest() {
+        let mut table = cheating_table(Penalty::PlayLowest);
+
+        // test
+        let result = play_round(&mut table);
+
+        assert_eq!(result, Ok((12, String::from("cheater"))));
+        let game = table.game.as_ref().unwrap();
+        assert_eq!(game.rounds()[0].bids, vec![2, 1]);
+        assert_eq!(game.hand(0).cards, vec![4,6,8]);
+        assert_eq!(table.players[0].player_stats.num_illegal_bids, 1);
+    }
+
+    #[test]
+    fn test_illegal_bid_disqualifies() {
+        let mut table = cheating_table(Penalty::Disqualify);
+
+        // test
+        play_round(&mut table).unwrap();
+        play_round(&mut table).unwrap();
+
+        let game = table.game.as_ref().unwrap();
+        assert_eq!(game.rounds()[1].bids[0], FORFEIT_OFFER);
+        assert_eq!(game.hand(0).cards, vec![6,8]);
+        assert!(game.is_disqualified(0));
+        assert!(table.players[0].is_disqualified());
+        assert_eq!(table.players[0].player_stats.num_illegal_bids, 1);
+    }
+
+    #[test]
+    fn test_play_next_round_after_deal() {
+        let p1 = Player{name: String::from("mozart"), .. Player::new()};
+        let p2 = Player{name: String::from("beethoven"), .. Player::new()};
+        let mut table = Table{players: vec![p1, p2], .. Table::new()};
+        let config = Config::new(2, 2, 12);
+        assert_eq!(play_next_round(&mut table), Err(String::from("no game in progress")));
+        deal_to_table(&config, &mut table);
+        for _round in 0..4 {
+            play_next_round(&mut table).unwrap();
+        }
+        assert_eq!(play_next_round(&mut table), Err(String::from("no prize card left")));
+        table.games_played = 1;
+
+        // test
+        deal_to_table(&config, &mut table);
+
+        assert!(play_next_round(&mut table).is_ok());
+    }
+    #[test]
+    fn test_rank_game_shared_places() {
+        let p1 = Player{name: String::from("mozart"), .. Player::new()};
+        let p2 = Player{name: String::from("beethoven"), .. Player::new()};
+        let p3 = Player{name: String::from("chopin"), .. Player::new()};
+        let p4 = Player{name: String::from("liszt"), .. Player::new()};
+        let mut players: Vec<Player> = vec![p1, p2, p3, p4];
+        players[0].player_stats.total_for_game = 10;
+        players[1].player_stats.total_for_game = 30;
+        players[2].player_stats.total_for_game = 10;
+        players[3].player_stats.total_for_game = 5;
+
+        // test
+        let result = rank_game(&players);
+
+        let places: Vec<(&str, u32)> = result.iter().map(|p| (p.name.as_str(), p.place)).collect();
+        assert_eq!(places, vec![("beethoven", 1), ("mozart", 2), ("chopin", 2), ("liszt", 4)]);
+    }
+
+    #[test]
+    fn test_rank_game_disqualified_last() {
+        let p1 = Player{name: String::from("mozart"), .. Player::new()};
+        let p2 = Player{name: String::from("salieri"), .. Player::new()};
+        let mut players: Vec<Player> = vec![p1, p2];
+        players[1].player_stats.total_for_game = 30;
+        players[1].player_stats.disqualified = true;
+
+        // test
+        let result = rank_game(&players);
+
+        assert_eq!(result[0], Placement{name: String::from("mozart"), score: 0, place: 1});
+        assert_eq!(result[1], Placement{name: String::from("salieri"), score: 30, place: 2});
+    }
+
+    #[test]
+    fn test_determine_tourney_winner_basic() {
+        let p1 = Player{name: String::from("mozart"), .. Player::new()};
+        let mut p2 = Player{name: String::from("beethoven"), .. Player::new()};
+        let mut p3 = Player{name: String::from("liszt"), .. Player::new()};
+        p2.wins_game();
+        p3.wins_game();
+        p3.wins_game();
+        let players = vec![p1, p2, p3];
+
+        // test
+        let result = determine_tourney_winner(&players);
+
+        assert_eq!(result.name, "liszt");
+    }
+
+    #[test]
+    fn test_build_deck_basic() {
+        let num_cards = 5;
+
+        // test
+        let result = engine::build_deck(num_cards, &mut rand::thread_rng());
+
+        let num_cards_result = u32::try_from(result.len()).unwrap();
+        assert_eq!(num_cards_result, num_cards);
+        for i in 1..num_cards+1 {
+            assert!(result.contains(&i));
+        }
+    }
+
+    #[test]
+    fn test_deal_to_table_basic() {
+        let p1 = Player{name: String::from("mozart"), .. Player::new()};
+        let p2 = Player{name: String::from("beethoven"), .. Player::new()};
+        let players: Vec<Player> = vec![p1, p2];
+        let num_players = u32::try_from(players.len()).unwrap();
+        let mut table = Table{players, .. Table::new()};
+
+        const NUM_GAMES: u32 = 1;
+        const NUM_CARDS: u32 = 12;
+        let num_cards_per_hand = NUM_CARDS / (num_players + 1);
+        let config = Config{num_players, num_games: NUM_GAMES,
+                            num_cards: NUM_CARDS, num_cards_per_hand};
+
+        // test
+        deal_to_table(&config, &mut table);
+
+        let game = table.game.unwrap();
+        let num_cards_kitty = u32::try_from(game.kitty().cards.len()).unwrap();
+        assert_eq!(num_cards_kitty, num_cards_per_hand);
+        for seat in 0..game.num_players() {
+            let num_cards_player = u32::try_from(game.hand(seat).cards.len()).unwrap();
+            assert_eq!(num_cards_player, num_cards_per_hand);
+        }
+    }
+
+    #[test]
+    fn test_deal_to_table_duplicate() {
+        let p1 = Player{name: String::from("mozart"), .. Player::new()};
+        let p2 = Player{name: String::from("beethoven"), .. Player::new()};
+        let p3 = Player{name: String::from("chopin"), .. Player::new()};
+        let mut table = Table{players: vec![p1, p2, p3], duplicate: Some(Duplicate::Rotations), .. Table::new()};
+        let config = Config::new(3, 2, 20);
+        deal_to_table(&config, &mut table);
+        let first = table.game.take().unwrap();
+        table.games_played = 1;
+
+        // test
+        deal_to_table(&config, &mut table);
+
+        let game = table.game.as_ref().unwrap();
+        assert_eq!(game.kitty(), first.kitty());
+        assert_eq!(game.hand(0), first.hand(1));
+        assert_eq!(game.hand(2), first.hand(0));
+        assert_eq!(tourney_length(&config, &table), 6);
+        table.games_played = 3;
+        deal_to_table(&config, &mut table);
+        assert_ne!(table.game.unwrap().kitty(), first.kitty());
+    }
+
+    #[test]
+    fn test_duplicate_new_hand_each_replay() {
+        let names = ["mozart", "beethoven", "chopin"];
+        let players = names.iter().map(|name| Player{name: String::from(*name), .. Player::new()}).collect();
+        let mut table = Table{players, duplicate: Some(Duplicate::Rotations), .. Table::new()};
+        let config = Config::new(3, 1, 20);
+        let mut hands: Vec<Vec<Hand>> = vec![vec![]; names.len()];
+
+        // test
+        for replay in 0..3 {
+            table.games_played = replay;
+            change_seats(&mut table);
+            deal_to_table(&config, &mut table);
+            let game = table.game.as_ref().unwrap();
+            for (seat, player) in table.players.iter().enumerate() {
+                let index = names.iter().position(|name| *name == player.name).unwrap();
+                hands[index].push(game.hand(seat).clone());
+            }
+        }
+
+        for (name, held) in names.iter().zip(&hands) {
+            assert!(held[0] != held[1] && held[1] != held[2] && held[0] != held[2], "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_run_tourney_rotates_seats() {
+        let p1 = Player{name: String::from("mozart"), .. Player::new()};
+        let p2 = Player{name: String::from("beethoven"), .. Player::new()};
+        let p3 = Player{name: String::from("chopin"), .. Player::new()};
+        let mut table = Table{players: vec![p1, p2, p3], seating: Some(Seating::Rotate), .. Table::new()};
+        let config = Config::new(3, 3, 12);
+
+        // test
+        run_tourney(&config, &mut table);
+
+        for p in &table.players {
+            let mut seats = p.player_stats.game_seats.clone();
+            seats.sort();
+            assert_eq!(seats, vec![0, 1, 2], "{}", p.name);
+        }
+        assert_eq!(table.players[0].player_stats.game_seats, vec![2, 1, 0]);
+    }
+
+    #[test]
+    fn test_get_bids_basic() {
+        let mut table = dealt_table(&["mozart", "beethoven"], vec![vec![10,11,12], vec![15,16,17]], vec![20], 20);
+        let game = table.game.as_mut().unwrap();
+        game.reveal_prize();
+
+        // test
+        get_bids(game, Penalty::ForfeitRound, &mut table.players, false).unwrap();
+
+        assert!(game.awaiting_bids().is_empty());
+        let round = game.resolve_round().unwrap().clone();
+        assert_eq!(round.prize_card, 20);
+        assert_eq!(round.bids, vec![10, 15]);
+        assert_eq!(game.hand(0).cards, vec![11, 12]);
+        assert_eq!(game.hand(1).cards, vec![16, 17]);
+    }
+    #[test]
+    fn test_play_round_basic() {
+        let hands = vec![vec![1,2,3], vec![4,5,6], vec![7,8,9]];
+        let mut table = dealt_table(&["mozart", "beethoven", "chopin"], hands, vec![10,11,12], 12);
+
+        // test
+        let (prize_card, winner_name) = play_round(&mut table).unwrap();
+
+        assert_eq!("chopin", winner_name);
+        assert_eq!(12, prize_card);
+    }
+    #[test]
+    fn test_update_round_winner_basic() {
+        let p1 = Player{name: String::from("mozart"), .. Player::new()};
+        let p2 = Player{name: String::from("beethoven"), .. Player::new()};
+        let p3 = Player{name: String::from("chopin"), .. Player::new()};
+        let players: Vec<Player> = vec![p1, p2, p3];
+
+        let mut table = Table{players, .. Table::new()};
+        let prize_card = 12;
+        let winner_name = String::from("chopin");
+
+        // test
+        update_round_winner(&mut table, prize_card, winner_name);
+
+        let winner = &table.players[2];
+        assert_eq!(0, winner.player_stats.num_games_won);
+        assert_eq!(1, winner.player_stats.num_rounds_won);
+        assert_eq!(12, winner.player_stats.total_for_game);
+    }
+
+    #[test]
+    fn test_update_game_winner_basic() {
+        let p1 = Player{name: String::from("mozart"), .. Player::new()};
+        let p2 = Player{name: String::from("beethoven"), .. Player::new()};
+        let p3 = Player{name: String::from("chopin"), .. Player::new()};
+        let players: Vec<Player> = vec![p1, p2, p3];
+
+        let mut table = Table{players, .. Table::new()};
+        let game_winner_name = String::from("chopin");
+
+        // test
+        update_game_winner(&mut table, game_winner_name);
+
+        let winner = &table.players[2];
+        assert_eq!(1, winner.player_stats.num_games_won);
+        assert_eq!(vec![1], winner.player_stats.game_placements);
+        assert_eq!(0, winner.player_stats.num_rounds_won);
+        assert_eq!(0, winner.player_stats.total_for_game);
+    }
+
+    #[test]
+    fn test_standings_basic() {
+        let p1 = Player{name: String::from("mozart"), .. Player::new()};
+        let p2 = Player{name: String::from("chopin"), .. Player::new()};
+        let mut table = Table{players: vec![p1, p2], games_played: 4, .. Table::new()};
+        table.players[1].player_stats.num_games_won = 3;
+
+        // test
+        let result = standings(&table);
+
+        assert_eq!(result, "mozart: played 4 won 0 (0.0%)\nchopin: played 4 won 3 (75.0%)\n");
+    }
+
+    // this is crazy! but comforting
+    #[test]
+    fn test_play_game_basic() {
+        let hands = vec![vec![3,4,7], vec![2,5,9], vec![1,6,8]];
+        let mut table = dealt_table(&["mozart", "beethoven", "chopin"], hands, vec![10,11,12], 12);
+        let num_players = u32::try_from(table.players.len()).unwrap();
+
+        const NUM_GAMES: u32 = 1;
+        const NUM_CARDS: u32 = 12;
+        let num_cards_per_hand = NUM_CARDS / (num_players + 1);
+        let config = Config{num_players, num_games: NUM_GAMES,
+                            num_cards: NUM_CARDS, num_cards_per_hand};
+        let use_dealer = false;
+
+        // test
+        let game_winner_name = play_game(&config, &mut table, use_dealer);
+
+        assert_eq!("mozart", game_winner_name);
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+
+use crate::game::Table;
+use crate::report;
+
+// --html: one HTML file, with inline CSS and SVG, written at the end of the tourney
+#[derive(Debug)]
+pub struct HtmlReport {
+    // the game shown round by round, from 1
+    game: u32,
+    // strategy -> (prize card, bid) -> how often
+    bids: BTreeMap<String, BTreeMap<(u32, u32), u32>>,
+    shown: Option<ShownGame>,
+    max_card: u32,
+}
+
+#[derive(Debug)]
+struct ShownGame {
+    players: Vec<String>,
+    // prize card, bid per seat, winning seat
+    rounds: Vec<(u32, Vec<u32>, Option<usize>)>,
+}
+
+const WIDTH: u32 = 360;
+const HEIGHT: u32 = 160;
+const NUM_BINS: u32 = 12;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:1em}td,th{padding:2px 8px;border-bottom:1px solid #ddd;text-align:right}\
+td:first-child,th:first-child{text-align:left}.won{background:#cfe8cf;font-weight:bold}\
+.charts{display:flex;flex-wrap:wrap;gap:1em}.chart{border:1px solid #ddd;padding:.5em}\
+svg text{font-size:10px}h3{margin:.2em 0;font-size:1em}";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;").replace('\'', "&#39;")
+}
+
+impl HtmlReport {
+    pub fn new(game: u32) -> HtmlReport {
+        HtmlReport{game: game.max(1), bids: BTreeMap::new(), shown: None, max_card: 0}
+    }
+
+    // after each game, while the finished game is still on the table; game is from 1
+    pub fn record_game(&mut self, table: &Table, game: u32) {
+        let state = match &table.game {
+            Some(state) => state,
+            None => return,
+        };
+        for round in state.rounds() {
+            for (seat, bid) in round.bids.iter().enumerate() {
+                let strategy = String::from(table.players[seat].strategy_name());
+                *self.bids.entry(strategy).or_default().entry((round.prize_card, *bid)).or_insert(0) += 1;
+                self.max_card = self.max_card.max(round.prize_card).max(*bid);
+            }
+        }
+        if game == self.game {
+            let players = table.players.iter().map(|p| String::from(p.name())).collect();
+            let rounds = state.rounds().iter().map(|r| (r.prize_card, r.bids.clone(), r.winner)).collect();
+            self.shown = Some(ShownGame{players, rounds});
+        }
+    }
+
+    pub fn write(&self, file: &str, table: &Table) -> io::Result<()> {
+        fs::write(file, self.render(table))
+    }
+
+    pub fn render(&self, table: &Table) -> String {
+        let mut html = String::new();
+        let _ = write!(html, "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>WarO tourney</title><style>{}</style></head><body>\n", STYLE);
+        let _ = writeln!(html, "<h1>WarO tourney</h1><p>{} games, seed {}</p>", table.games_played, table.seed);
+        self.render_standings(&mut html, table);
+        self.render_histograms(&mut html, table);
+        self.render_scatters(&mut html);
+        self.render_game(&mut html);
+        html.push_str("</body></html>\n");
+        html
+    }
+
+    fn render_standings(&self, html: &mut String, table: &Table) {
+        let report = report::build(table);
+        html.push_str("<h2>Standings</h2>\n<table><tr><th>player</th><th>strategy</th><th>games</th><th>wins</th><th>win rate</th><th>95% interval</th><th>points per game</th><th>average place</th></tr>\n");
+        let mut players: Vec<&report::PlayerReport> = report.players.iter().collect();
+        players.sort_by_key(|p| std::cmp::Reverse(p.wins));
+        for p in players {
+            let bar = (p.win_rate * 200.0).round() as u32;
+            let _ = writeln!(html, "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+                <td><svg width=\"260\" height=\"14\"><rect width=\"{}\" height=\"14\" fill=\"#4a7ab5\"/><text x=\"{}\" y=\"11\">{:.1}%</text></svg></td>\
+                <td>{:.1}-{:.1}%</td><td>{:.1}</td><td>{:.2}</td></tr>",
+                escape(&p.name), escape(&p.strategy), p.games, p.wins, bar, bar + 4, 100.0 * p.win_rate,
+                100.0 * p.win_interval.low, 100.0 * p.win_interval.high, p.points.mean, p.average_placement);
+        }
+        html.push_str("</table>\n");
+    }
+
+    fn render_histograms(&self, html: &mut String, table: &Table) {
+        html.push_str("<h2>Points per game</h2>\n<div class=\"charts\">\n");
+        let max_points = table.players.iter().flat_map(|p| p.player_stats.game_points.iter()).max().copied().unwrap_or(0);
+        let bin_width = (max_points / NUM_BINS + 1).max(1);
+        for player in &table.players {
+            let mut bins = vec![0u32; NUM_BINS as usize + 1];
+            for points in &player.player_stats.game_points {
+                bins[(points / bin_width) as usize] += 1;
+            }
+            let tallest = bins.iter().max().copied().unwrap_or(0).max(1);
+            let bar_width = WIDTH / bins.len() as u32;
+            let _ = write!(html, "<div class=\"chart\"><h3>{}</h3><svg width=\"{}\" height=\"{}\">", escape(player.name()), WIDTH, HEIGHT + 14);
+            for (index, count) in bins.iter().enumerate() {
+                let height = count * HEIGHT / tallest;
+                let x = index as u32 * bar_width;
+                let _ = write!(html, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#6a9f58\"><title>{}-{}: {} games</title></rect>",
+                               x, HEIGHT - height, bar_width - 1, height, index as u32 * bin_width, (index as u32 + 1) * bin_width - 1, count);
+                if index % 3 == 0 {
+                    let _ = write!(html, "<text x=\"{}\" y=\"{}\">{}</text>", x, HEIGHT + 12, index as u32 * bin_width);
+                }
+            }
+            html.push_str("</svg></div>\n");
+        }
+        html.push_str("</div>\n");
+    }
+
+    fn render_scatters(&self, html: &mut String) {
+        html.push_str("<h2>Bid against prize</h2>\n<div class=\"charts\">\n");
+        let scale = HEIGHT as f64 / self.max_card.max(1) as f64;
+        for (strategy, counts) in &self.bids {
+            let most = counts.values().max().copied().unwrap_or(1) as f64;
+            let _ = write!(html, "<div class=\"chart\"><h3>{}</h3><svg width=\"{}\" height=\"{}\">", escape(strategy), HEIGHT + 20, HEIGHT + 20);
+            let _ = write!(html, "<line x1=\"20\" y1=\"{0}\" x2=\"{1}\" y2=\"0\" stroke=\"#ccc\"/><text x=\"0\" y=\"10\">bid</text><text x=\"{1}\" y=\"{2}\" text-anchor=\"end\">prize</text>",
+                           HEIGHT, HEIGHT + 20, HEIGHT + 16);
+            for ((prize, bid), count) in counts {
+                let radius = 1.0 + 4.0 * (*count as f64 / most).sqrt();
+                let _ = write!(html, "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"#b5544a\" fill-opacity=\"0.6\"><title>prize {}, bid {}: {} times</title></circle>",
+                               20.0 + *prize as f64 * scale, HEIGHT as f64 - *bid as f64 * scale, radius, prize, bid, count);
+            }
+            html.push_str("</svg></div>\n");
+        }
+        html.push_str("</div>\n");
+    }
+
+    fn render_game(&self, html: &mut String) {
+        let _ = writeln!(html, "<h2>Game {}, round by round</h2>", self.game);
+        let shown = match &self.shown {
+            Some(shown) => shown,
+            None => {
+                html.push_str("<p>not played</p>\n");
+                return;
+            }
+        };
+        html.push_str("<table><tr><th>round</th><th>prize</th>");
+        for name in &shown.players {
+            let _ = write!(html, "<th>{}</th>", escape(name));
+        }
+        html.push_str("</tr>\n");
+        let mut scores = vec![0; shown.players.len()];
+        for (index, (prize, bids, winner)) in shown.rounds.iter().enumerate() {
+            if let Some(winner) = winner {
+                scores[*winner] += prize;
+            }
+            let _ = write!(html, "<tr><td>{}</td><td>{}</td>", index + 1, prize);
+            for (seat, bid) in bids.iter().enumerate() {
+                let class = if *winner == Some(seat) { " class=\"won\"" } else { "" };
+                let _ = write!(html, "<td{}>{} <small>({})</small></td>", class, bid, scores[seat]);
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table><p>each cell is the bid, with the running score in brackets</p>\n");
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::game;
+    use crate::player::Player;
+
+    #[test]
+    fn test_escape_basic() {
+        assert_eq!(escape("<b>Tom & \"Jerry\"</b>"), "&lt;b&gt;Tom &amp; &quot;Jerry&quot;&lt;/b&gt;");
+    }
+
+    #[test]
+    fn test_render_basic() {
+        let players = vec![Player::new_from_json("mozart", "max_card"), Player::new_from_json("<salieri>", "min_card")];
+        let mut table = Table{players, .. Table::new()};
+        table.html = Some(HtmlReport::new(2));
+        let config = Config::new(2, 3, 12);
+        game::run_tourney(&config, &mut table);
+
+        // test
+        let result = table.html.as_ref().unwrap().render(&table);
+
+        assert!(result.contains("<h2>Standings</h2>"));
+        assert!(result.contains("&lt;salieri&gt;"));
+        assert!(!result.contains("<salieri>"));
+        assert!(result.contains("<h3>max_card</h3>"));
+        assert!(result.contains("<h2>Game 2, round by round</h2>"));
+        assert!(!result.contains("not played"));
+        // no external assets
+        assert!(!result.contains("src="));
+        assert!(!result.contains("href="));
+    }
+}
@@ -0,0 +1,632 @@
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config;
+use crate::game;
+use crate::player::Player;
+use crate::strategy::external::ExternalBot;
+
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+// the seat a client plays through POST /games/{id}/step
+pub use crate::strategy::API;
+const API_TIMEOUT_MS: u64 = 10 * 60 * 1000;
+// a game nobody has asked about for this long is abandoned, and may be evicted
+const IDLE_MS: u64 = API_TIMEOUT_MS;
+const STEP_TIMEOUT_MS: u64 = 30 * 1000;
+const MAX_BODY: usize = 1024 * 1024;
+// simulations and games kept for polling; the oldest finished ones make room for new ones
+const MAX_KEPT: usize = 100;
+
+// strategies that would read the server's stdin, wait for a socket, or run a command
+const FORBIDDEN: [&str; 3] = ["console", "remote", "external"];
+
+// the config keys a request may set. the others write files on the server (checkpoint,
+// ratings), listen on a port (spectators), prompt at its console (hot_seat) or play more
+// games than were asked for (duplicate, seating). a new config key goes in one list or the other
+pub(crate) const ALLOWED_KEYS: [&str; 5] = ["num_cards", "num_games", "players", "seed", "illegal_bid_penalty"];
+pub(crate) const REFUSED_KEYS: [&str; 6] = ["spectators", "checkpoint", "ratings", "duplicate", "seating", "hot_seat"];
+const ALLOWED_PLAYER_KEYS: [&str; 2] = ["name", "strategy"];
+
+#[derive(Debug, Clone)]
+struct Simulation {
+    status: &'static str,
+    num_games: u32,
+    standings: Vec<Value>,
+    winner: Option<String>,
+}
+
+// what the api seat has been told so far, rebuilt from its protocol messages
+#[derive(Debug, Default)]
+struct SessionState {
+    seat: String,
+    hand: Vec<u32>,
+    prize_card: Option<u32>,
+    // the round of the bid the engine is waiting for, which the reply must carry
+    awaiting_bid: Option<u64>,
+    rounds: Vec<Value>,
+    scores: Value,
+    winner: Option<String>,
+    finished: bool,
+}
+
+struct GameSession {
+    state: Mutex<SessionState>,
+    changed: Condvar,
+    // None once the game is abandoned
+    bids: Mutex<Option<io::PipeWriter>>,
+    last_seen: Mutex<Instant>,
+}
+
+impl GameSession {
+    fn touch(&self) {
+        *self.last_seen.lock().unwrap() = Instant::now();
+    }
+
+    fn is_idle(&self) -> bool {
+        self.last_seen.lock().unwrap().elapsed() > Duration::from_millis(IDLE_MS)
+    }
+
+    // closing the api seat's end makes the engine forfeit its remaining bids at once
+    fn abandon(&self) {
+        self.bids.lock().unwrap().take();
+    }
+}
+
+#[derive(Default)]
+struct Store {
+    next_id: u64,
+    simulations: HashMap<u64, Simulation>,
+    games: HashMap<u64, Arc<GameSession>>,
+}
+
+impl Store {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+// false when the map is full of entries still in play
+fn make_room<T, F: Fn(&T) -> bool>(entries: &mut HashMap<u64, T>, max_kept: usize, finished: F) -> bool {
+    while entries.len() >= max_kept {
+        let oldest = entries.iter().filter(|(_, entry)| finished(entry)).map(|(id, _)| *id).min();
+        match oldest {
+            Some(id) => entries.remove(&id),
+            None => return false,
+        };
+    }
+    true
+}
+
+struct Response {
+    status: u16,
+    body: Value,
+}
+
+fn respond(status: u16, body: Value) -> Response {
+    Response{status, body}
+}
+
+fn error(status: u16, message: &str) -> Response {
+    respond(status, json!({"error": message}))
+}
+
+pub fn serve(address: &str) {
+    let listener = TcpListener::bind(address).expect("Unable to listen");
+    tracer!("TRACER http api on {}", listener.local_addr().unwrap());
+    run(listener);
+}
+
+pub fn run(listener: TcpListener) {
+    let store = Arc::new(Mutex::new(Store::default()));
+    for stream in listener.incoming().flatten() {
+        let store = Arc::clone(&store);
+        thread::spawn(move || handle_connection(stream, &store));
+    }
+}
+
+fn handle_connection(stream: TcpStream, store: &Arc<Mutex<Store>>) {
+    let mut reader = BufReader::new(stream);
+    let response = match read_request(&mut reader) {
+        Ok((method, path, body)) => route(&method, &path, &body, store),
+        Err(message) => error(400, &message),
+    };
+    let _ = write_response(reader.get_mut(), &response);
+}
+
+fn read_request<R: BufRead>(reader: &mut R) -> Result<(String, String, String), String> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).map_err(|e| e.to_string())?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().ok_or("empty request")?.to_string();
+    let path = parts.next().ok_or("missing path")?.to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).map_err(|e| e.to_string())? == 0 {
+            break;
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().map_err(|_| "bad content-length")?;
+            }
+        }
+    }
+    if content_length > MAX_BODY {
+        return Err(String::from("body too large"));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    let body = String::from_utf8(body).map_err(|_| "body is not UTF-8")?;
+    Ok((method, path, body))
+}
+
+fn write_response<W: Write>(output: &mut W, response: &Response) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        404 => "Not Found",
+        409 => "Conflict",
+        _ => "Error",
+    };
+    let body = response.body.to_string();
+    write!(output, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+           response.status, reason, body.len(), body)?;
+    output.flush()
+}
+
+fn route(method: &str, path: &str, body: &str, store: &Arc<Mutex<Store>>) -> Response {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match (method, segments.as_slice()) {
+        ("POST", ["simulations"]) => create_simulation(body, store),
+        ("GET", ["simulations", id]) => with_id(id, |id| get_simulation(id, store)),
+        ("POST", ["games"]) => create_game(body, store),
+        ("GET", ["games", id]) => with_id(id, |id| get_game(id, store)),
+        ("POST", ["games", id, "step"]) => with_id(id, |id| step_game(id, body, store)),
+        _ => error(404, "no such route"),
+    }
+}
+
+fn with_id<F: FnOnce(u64) -> Response>(id: &str, f: F) -> Response {
+    match id.parse() {
+        Ok(id) => f(id),
+        Err(_) => error(404, "no such id"),
+    }
+}
+
+fn check_body(body: &str) -> Result<(), String> {
+    let value: Value = serde_json::from_str(body).map_err(|e| e.to_string())?;
+    let keys = value.as_object().ok_or("expected a JSON object")?.keys();
+    for key in keys {
+        if REFUSED_KEYS.contains(&key.as_str()) {
+            return Err(format!("{:?} is not available over http", key));
+        }
+        if !ALLOWED_KEYS.contains(&key.as_str()) {
+            return Err(format!("unknown key {:?}", key));
+        }
+    }
+    for player in value["players"].as_array().unwrap_or(&vec![]) {
+        for key in player.as_object().map(|p| p.keys().collect()).unwrap_or_else(Vec::new) {
+            if !ALLOWED_PLAYER_KEYS.contains(&key.as_str()) {
+                return Err(format!("{:?} is not available over http", key));
+            }
+        }
+        let strategy = player["strategy"].as_str().unwrap_or("");
+        if FORBIDDEN.contains(&strategy) {
+            return Err(format!("strategy {:?} is not available over http", strategy));
+        }
+    }
+    Ok(())
+}
+
+fn create_simulation(body: &str, store: &Arc<Mutex<Store>>) -> Response {
+    if let Err(message) = check_body(body) {
+        return error(400, &message);
+    }
+    let (mut table, config) = match config::build_from_str(body) {
+        Ok(built) => built,
+        Err(message) => return error(400, &message),
+    };
+    if table.players.iter().any(|p| p.strategy_name() == API) {
+        return error(400, "an api seat needs POST /games");
+    }
+
+    let id = {
+        let mut store = store.lock().unwrap();
+        if !make_room(&mut store.simulations, MAX_KEPT, |s| s.status != "running") {
+            return error(503, "too many simulations running");
+        }
+        let id = store.next_id();
+        let simulation = Simulation{status: "running", num_games: config.num_games, standings: vec![], winner: None};
+        store.simulations.insert(id, simulation);
+        id
+    };
+
+    let store = Arc::clone(store);
+    thread::spawn(move || {
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            game::run_quiet_tourney(&config, &mut table);
+            for player in &mut table.players {
+                player.leave_table();
+            }
+            standings(&table, config.num_games)
+        }));
+        let mut store = store.lock().unwrap();
+        let simulation = store.simulations.get_mut(&id).unwrap();
+        match outcome {
+            Ok((standings, winner)) => {
+                simulation.status = "done";
+                simulation.standings = standings;
+                simulation.winner = Some(winner);
+            }
+            Err(_) => simulation.status = "failed",
+        }
+    });
+
+    respond(201, json!({"id": id}))
+}
+
+fn standings(table: &game::Table, num_games: u32) -> (Vec<Value>, String) {
+    let mut players: Vec<&Player> = table.players.iter().collect();
+    players.sort_by_key(|p| std::cmp::Reverse(p.num_games_won()));
+    let standings = players.iter().map(|p| {
+        let win_rate = if num_games == 0 { 0.0 } else { f64::from(p.num_games_won()) / f64::from(num_games) };
+        json!({"name": p.name(), "games_won": p.num_games_won(), "win_rate": win_rate})
+    }).collect();
+    let winner = players.first().map(|p| String::from(p.name())).unwrap_or_default();
+    (standings, winner)
+}
+
+fn get_simulation(id: u64, store: &Arc<Mutex<Store>>) -> Response {
+    let store = store.lock().unwrap();
+    match store.simulations.get(&id) {
+        Some(simulation) => respond(200, json!({
+            "id": id,
+            "status": simulation.status,
+            "num_games": simulation.num_games,
+            "standings": simulation.standings,
+            "winner": simulation.winner,
+        })),
+        None => error(404, "no such simulation"),
+    }
+}
+
+fn create_game(body: &str, store: &Arc<Mutex<Store>>) -> Response {
+    if let Err(message) = check_body(body) {
+        return error(400, &message);
+    }
+    let (mut table, mut config) = match config::build_from_str(body) {
+        Ok(built) => built,
+        Err(message) => return error(400, &message),
+    };
+    let seats: Vec<usize> = (0..table.players.len()).filter(|i| table.players[*i].strategy_name() == API).collect();
+    if seats.len() != 1 {
+        return error(400, "exactly one player needs \"strategy\": \"api\"");
+    }
+    config.num_games = 1;
+
+    // the engine talks to the api seat over a pair of pipes, as it would to a bot
+    let (bids, messages, to_seat, session) = match open_session(table.players[seats[0]].name()) {
+        Ok(opened) => opened,
+        Err(e) => return error(500, &e.to_string()),
+    };
+    table.players[seats[0]].take_seat(ExternalBot::from_pipes(bids, to_seat, API_TIMEOUT_MS));
+
+    let id = {
+        let mut store = store.lock().unwrap();
+        for session in store.games.values().filter(|g| g.is_idle()) {
+            session.abandon();
+        }
+        if !make_room(&mut store.games, MAX_KEPT, |g| g.state.lock().unwrap().finished || g.is_idle()) {
+            return error(503, "too many games in play");
+        }
+        let id = store.next_id();
+        store.games.insert(id, Arc::clone(&session));
+        id
+    };
+
+    let reader_session = Arc::clone(&session);
+    thread::spawn(move || follow_engine(messages, &reader_session));
+    thread::spawn(move || {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+            game::run_quiet_tourney(&config, &mut table);
+            for player in &mut table.players {
+                player.leave_table();
+            }
+        }));
+    });
+    let state = wait_for_turn(&session);
+    respond(201, game_json(id, &state))
+}
+
+type Session = (io::PipeReader, io::PipeReader, io::PipeWriter, Arc<GameSession>);
+
+// returns the bids read by the engine, the messages it writes to the seat, and the session fed by both
+fn open_session(seat: &str) -> io::Result<Session> {
+    let (bids_reader, bids_writer) = io::pipe()?;
+    let (messages_reader, messages_writer) = io::pipe()?;
+    let state = SessionState{seat: String::from(seat), scores: json!([]), .. SessionState::default()};
+    let session = Arc::new(GameSession{state: Mutex::new(state), changed: Condvar::new(), bids: Mutex::new(Some(bids_writer)),
+                                       last_seen: Mutex::new(Instant::now())});
+    Ok((bids_reader, messages_reader, messages_writer, session))
+}
+
+// applies each protocol message sent to the api seat
+fn follow_engine(messages: io::PipeReader, session: &GameSession) {
+    for line in BufReader::new(messages).lines() {
+        let message: Value = match line.ok().and_then(|l| serde_json::from_str(&l).ok()) {
+            Some(message) => message,
+            None => break,
+        };
+        let mut state = session.state.lock().unwrap();
+        match message["type"].as_str().unwrap_or("") {
+            "deal" | "prize_revealed" => {
+                state.hand = message["hand"].as_array().unwrap_or(&vec![]).iter().filter_map(|c| c.as_u64()).map(|c| c as u32).collect();
+                if let Some(prize_card) = message["prize_card"].as_u64() {
+                    state.prize_card = Some(prize_card as u32);
+                }
+            }
+            "bid_request" => state.awaiting_bid = message["round"].as_u64(),
+            "round_result" => {
+                state.prize_card = None;
+                state.rounds.push(json!({"prize_card": message["prize_card"], "bids": message["bids"], "winner": message["winner"]}));
+            }
+            "game_over" => {
+                state.scores = message["scores"].clone();
+                state.winner = message["winner"].as_str().map(String::from);
+            }
+            "tourney_over" => state.finished = true,
+            _ => {}
+        }
+        session.changed.notify_all();
+    }
+    session.state.lock().unwrap().finished = true;
+    session.changed.notify_all();
+}
+
+fn wait_for_turn(session: &GameSession) -> std::sync::MutexGuard<'_, SessionState> {
+    let state = session.state.lock().unwrap();
+    let (state, _timeout) = session.changed
+                                   .wait_timeout_while(state, Duration::from_millis(STEP_TIMEOUT_MS), |s| s.awaiting_bid.is_none() && !s.finished)
+                                   .unwrap();
+    state
+}
+
+fn game_json(id: u64, state: &SessionState) -> Value {
+    json!({
+        "id": id,
+        "seat": state.seat,
+        "status": if state.finished { "finished" } else if state.awaiting_bid.is_some() { "awaiting_bid" } else { "playing" },
+        "hand": state.hand,
+        "prize_card": state.prize_card,
+        "rounds": state.rounds,
+        "scores": state.scores,
+        "winner": state.winner,
+    })
+}
+
+fn get_game(id: u64, store: &Arc<Mutex<Store>>) -> Response {
+    let session = match store.lock().unwrap().games.get(&id) {
+        Some(session) => Arc::clone(session),
+        None => return error(404, "no such game"),
+    };
+    session.touch();
+    let state = session.state.lock().unwrap();
+    respond(200, game_json(id, &state))
+}
+
+fn step_game(id: u64, body: &str, store: &Arc<Mutex<Store>>) -> Response {
+    let session = match store.lock().unwrap().games.get(&id) {
+        Some(session) => Arc::clone(session),
+        None => return error(404, "no such game"),
+    };
+    session.touch();
+    let bid = match serde_json::from_str::<Value>(body).ok().and_then(|v| v["bid"].as_u64()) {
+        Some(bid) => bid,
+        None => return error(400, "expected {\"bid\": N}"),
+    };
+
+    {
+        let mut state = session.state.lock().unwrap();
+        let round = match state.awaiting_bid.take() {
+            Some(round) => round,
+            None => return error(409, "not waiting for a bid"),
+        };
+        let mut bids = session.bids.lock().unwrap();
+        let sent = match bids.as_mut() {
+            Some(bids) => writeln!(bids, "{}", json!({"round": round, "bid": bid})).is_ok(),
+            None => false,
+        };
+        if !sent {
+            return error(409, "the game is over");
+        }
+    }
+
+    // the engine checks the bid; an illegal one is penalised like any other
+    let state = wait_for_turn(&session);
+    respond(200, game_json(id, &state))
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+    use crate::config::Config;
+    use crate::game::Table;
+    use crate::strategy::Strategy;
+
+    #[allow(dead_code)]
+    fn start() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || run(listener));
+        address
+    }
+
+    #[allow(dead_code)]
+    fn request(address: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "{} {} HTTP/1.1\r\nHost: test\r\nContent-Length: {}\r\n\r\n{}", method, path, body.len(), body).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = response.split("\r\n\r\n").nth(1).unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn test_simulation() {
+        let address = start();
+        let body = r#"{"num_cards": 12, "num_games": 4,
+                       "players": [{"name": "bach", "strategy": "min_card"}, {"name": "liszt", "strategy": "max_card"}]}"#;
+
+        // test
+        let (status, created) = request(address, "POST", "/simulations", body);
+
+        assert_eq!(status, 201);
+        let path = format!("/simulations/{}", created["id"]);
+        let mut result = request(address, "GET", &path, "").1;
+        while result["status"] == "running" {
+            thread::sleep(Duration::from_millis(10));
+            result = request(address, "GET", &path, "").1;
+        }
+        assert_eq!(result["status"], "done");
+        assert_eq!(result["standings"].as_array().unwrap().len(), 2);
+        let games_won: u64 = result["standings"].as_array().unwrap().iter().map(|s| s["games_won"].as_u64().unwrap()).sum();
+        assert_eq!(games_won, 4);
+    }
+
+    #[test]
+    fn test_simulation_rejects_external() {
+        let address = start();
+        let body = r#"{"num_cards": 12, "num_games": 4,
+                       "players": [{"name": "sh", "strategy": "external", "command": ["sh"]}]}"#;
+
+        // test
+        let (status, _) = request(address, "POST", "/simulations", body);
+
+        assert_eq!(status, 400);
+    }
+
+    #[test]
+    fn test_make_room_evicts_oldest_finished() {
+        let mut entries: HashMap<u64, &str> = vec![(1, "done"), (2, "running"), (3, "done")].into_iter().collect();
+
+        // test
+        let result = make_room(&mut entries, 2, |e| *e == "done");
+
+        assert!(result);
+        let mut kept: Vec<u64> = entries.keys().copied().collect();
+        kept.sort();
+        assert_eq!(kept, vec![2]);
+        entries.insert(4, "running");
+        assert!(!make_room(&mut entries, 2, |e| *e == "done"));
+    }
+
+    #[test]
+    fn test_game_steps() {
+        let address = start();
+        let body = r#"{"num_cards": 12, "num_games": 1,
+                       "players": [{"name": "you", "strategy": "api"}, {"name": "bach", "strategy": "next_card"}]}"#;
+
+        // test
+        let (status, mut game) = request(address, "POST", "/games", body);
+
+        assert_eq!(status, 201);
+        let path = format!("/games/{}/step", game["id"]);
+        let mut steps = 0;
+        while game["status"] == "awaiting_bid" {
+            assert_eq!(game["hand"].as_array().unwrap().len(), 4 - steps);
+            let bid = format!(r#"{{"bid": {}}}"#, game["hand"][0]);
+            game = request(address, "POST", &path, &bid).1;
+            steps += 1;
+        }
+        assert_eq!(steps, 4);
+        assert_eq!(game["status"], "finished");
+        assert_eq!(game["rounds"].as_array().unwrap().len(), 4);
+        assert!(game["winner"].is_string());
+
+        let (status, _) = request(address, "POST", &path, r#"{"bid": 1}"#);
+        assert_eq!(status, 409);
+    }
+
+    #[test]
+    fn test_simulation_rejects_checkpoint() {
+        let address = start();
+        let file = std::env::temp_dir().join(format!("waro_http_checkpoint_{}.json", std::process::id()));
+        let body = format!(r#"{{"num_cards": 12, "num_games": 4, "checkpoint": {{"file": {:?}, "every_games": 1}},
+                               "players": [{{"name": "bach", "strategy": "min_card"}}, {{"name": "liszt", "strategy": "max_card"}}]}}"#,
+                           file.to_string_lossy());
+
+        // test
+        let (status, result) = request(address, "POST", "/simulations", &body);
+
+        assert_eq!(status, 400);
+        assert_eq!(result["error"], "\"checkpoint\" is not available over http");
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn test_game_rejects_seating() {
+        let address = start();
+        let body = r#"{"num_cards": 12, "num_games": 1, "seating": "rotate",
+                       "players": [{"name": "you", "strategy": "api"}, {"name": "bach", "strategy": "next_card"}]}"#;
+
+        // test
+        let (status, _) = request(address, "POST", "/games", body);
+
+        assert_eq!(status, 400);
+    }
+
+    #[test]
+    fn test_abandoned_game_ends() {
+        let (bids, messages, to_seat, session) = open_session("you").unwrap();
+        let mut table = Table{players: vec![Player::new_with_strategy("you", Strategy::new_remote(API_TIMEOUT_MS)),
+                                            Player::new_from_json("bach", "next_card")], .. Table::new()};
+        table.players[0].take_seat(ExternalBot::from_pipes(bids, to_seat, API_TIMEOUT_MS));
+        let follower = Arc::clone(&session);
+        thread::spawn(move || follow_engine(messages, &follower));
+        *session.last_seen.lock().unwrap() = Instant::now() - Duration::from_millis(IDLE_MS + 1);
+
+        // test
+        assert!(session.is_idle());
+        session.abandon();
+        game::run_quiet_tourney(&Config::new(2, 1, 12), &mut table);
+
+        // every bid of the api seat was forfeited, without waiting out its timeout
+        assert_eq!(table.players[0].player_stats().num_illegal_bids, 4);
+    }
+
+    #[test]
+    fn test_game_rejects_ratings() {
+        let address = start();
+        let file = std::env::temp_dir().join(format!("waro_http_ratings_{}.json", std::process::id()));
+        let body = format!(r#"{{"num_cards": 12, "num_games": 1, "ratings": {{"file": {:?}}},
+                               "players": [{{"name": "you", "strategy": "api"}}, {{"name": "bach", "strategy": "next_card"}}]}}"#,
+                           file.to_string_lossy());
+
+        // test
+        let (status, result) = request(address, "POST", "/games", &body);
+
+        assert_eq!(status, 400);
+        assert_eq!(result["error"], "\"ratings\" is not available over http");
+        assert!(!file.exists());
+    }
+}
@@ -0,0 +1,51 @@
+
+// Ctrl-C during a tourney: the first one lets the current game finish, the second one aborts
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static INTERRUPTS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn requested() -> bool {
+    INTERRUPTS.load(Ordering::SeqCst) > 0
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+    use std::{mem, ptr};
+
+    const ABORT_STATUS: i32 = 130;
+
+    // only what is safe inside a signal handler: atomics, write(2) and _exit(2)
+    extern "C" fn on_interrupt(_signum: libc::c_int) {
+        let first = INTERRUPTS.fetch_add(1, Ordering::SeqCst) == 0;
+        let notice: &[u8] = if first {
+            b"\ninterrupted: finishing the current game, Ctrl-C again to abort\n"
+        } else {
+            b"\naborted\n"
+        };
+        unsafe {
+            libc::write(libc::STDERR_FILENO, notice.as_ptr() as *const libc::c_void, notice.len());
+            if !first {
+                libc::_exit(ABORT_STATUS);
+            }
+        }
+    }
+
+    pub fn install() {
+        unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            // a read of stdin by a console player carries on after the first Ctrl-C
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(libc::SIGINT, &action, ptr::null_mut());
+        }
+    }
+}
+
+// without a handler, Ctrl-C stops the process as before
+pub fn install() {
+    #[cfg(unix)]
+    unix::install();
+}
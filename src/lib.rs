@@ -0,0 +1,48 @@
+
+// waro as a library: build a table from a config, deal, play rounds and
+// tourneys, and plug in strategies. the command line game is main.rs
+//
+// engine: the rules with no I/O, which also build for WebAssembly
+// player, game: players and the table that runs games and tourneys on top of the engine
+// strategy: the built-in strategies, external bots, the console and custom Bidders
+// config: tables from JSON
+// checkpoint, interrupt, report, csv, html: long simulations and their results
+// analyze: how each strategy bids
+// duplicate: replaying each deal with the hands moved around the table
+// ratings: Elo ratings kept across tourneys
+// matrix, tournament: every strategy against every other, and pools of entrants over many tables
+// server, client, arena, http, spectator: network play
+// trace: the TRACER lines on stdout
+
+#[macro_use]
+mod trace;
+
+pub mod analyze;
+pub mod arena;
+pub mod checkpoint;
+pub mod client;
+pub mod config;
+pub mod csv;
+pub mod duplicate;
+pub mod engine;
+pub mod game;
+pub mod html;
+pub mod http;
+pub mod interrupt;
+pub mod matrix;
+pub mod player;
+pub mod ratings;
+pub mod report;
+pub mod server;
+pub mod spectator;
+pub mod strategy;
+pub mod tournament;
+
+#[cfg(target_arch = "wasm32")]
+mod wasm;
+
+pub use config::Config;
+pub use engine::{GameState, Hand, IllegalBid};
+pub use game::Table;
+pub use player::{Penalty, Player, PlayerStats};
+pub use strategy::Strategy;
@@ -1,26 +1,158 @@
-
 use std::env;
+use std::io;
 
-mod config;
-
-use config::player::game;
+use waro::Table;
+use waro::csv::CsvExport;
+use waro::html::HtmlReport;
+use waro::{analyze, arena, checkpoint, client, config, game, http, interrupt, matrix, ratings, server, tournament};
+use waro::strategy::console;
 
 fn emit_banner() {
-    for _i in 1..20 {
-        println!("");
-    }
+    console::clear_screen(&mut io::stdout());
     println!("----------------------------------");
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let config_file = &args[1];
-    let (mut table, config) = config::build_from_json(config_file);
+fn emit_usage() {
+    println!("usage:");
+    println!("  waro [json config file]                  play a tourney");
+    println!("  waro --resume [checkpoint file]          continue a tourney from its checkpoint");
+    println!("  waro serve [json config file] [address]  host a table with remote seats (default {})", server::DEFAULT_ADDRESS);
+    println!("  waro join [address] [name]               take a remote seat");
+    println!("  waro arena [json arena file] [address]   run a bot arena (default {})", arena::DEFAULT_ADDRESS);
+    println!("  waro matrix [json matrix file]           play strategies head to head");
+    println!("  waro tournament [json tournament file]   run a round robin or swiss tournament");
+    println!("  waro analyze [json config file]          profile how each strategy bids");
+    println!("  waro ratings [ratings file]              show the leaderboard");
+    println!("  waro http [address]                      serve the JSON API (default {})", http::DEFAULT_ADDRESS);
+    println!("options for a tourney:");
+    println!("  --csv-games [csv file]                   a line per player per game");
+    println!("  --csv-rounds [csv file]                  a line per round");
+    println!("  --html [html file]                       a report with charts, written at the end");
+    println!("  --html-game [game]                       the game the report shows round by round (default 1)");
+}
+
+// files written from the tourney loop, from --csv-games, --csv-rounds and --html
+struct Exports {
+    csv_games: Option<String>,
+    csv_rounds: Option<String>,
+    html: Option<String>,
+    html_game: u32,
+}
+
+impl Exports {
+    // takes the options out of args, wherever they are
+    fn from_args(args: &mut Vec<String>) -> Exports {
+        Exports {
+            csv_games: take_option(args, "--csv-games"),
+            csv_rounds: take_option(args, "--csv-rounds"),
+            html: take_option(args, "--html"),
+            html_game: take_option(args, "--html-game").and_then(|game| game.parse().ok()).unwrap_or(1),
+        }
+    }
+
+    fn attach(&self, table: &mut Table) -> bool {
+        if self.html.is_some() {
+            table.html = Some(HtmlReport::new(self.html_game));
+        }
+        if self.csv_games.is_none() && self.csv_rounds.is_none() {
+            return true;
+        }
+        match CsvExport::create(self.csv_games.as_deref(), self.csv_rounds.as_deref(), table) {
+            Ok(csv) => {
+                table.csv = Some(csv);
+                true
+            }
+            Err(e) => {
+                println!("could not create csv files: {}", e);
+                false
+            }
+        }
+    }
+
+    fn finish(&self, table: &Table) {
+        if let (Some(file), Some(html)) = (&self.html, &table.html) {
+            match html.write(file, table) {
+                Ok(()) => println!("TRACER report in {}", file),
+                Err(e) => println!("could not write {}: {}", file, e),
+            }
+        }
+    }
+}
+
+fn take_option(args: &mut Vec<String>, name: &str) -> Option<String> {
+    let index = args.iter().position(|arg| arg == name)?;
+    if index + 1 >= args.len() {
+        args.remove(index);
+        return None;
+    }
+    let value = args.remove(index + 1);
+    args.remove(index);
+    Some(value)
+}
+
+fn play(config_file: &str, exports: &Exports) {
+    let (mut table, config) = match config::build_from_json(config_file) {
+        Ok(built) => built,
+        Err(e) => {
+            println!("tourney cannot start: {}", e);
+            return;
+        }
+    };
+    if !exports.attach(&mut table) {
+        return;
+    }
 
     emit_banner();
 
     println!("TRACER config: {:?}", config);
     println!("TRACER table: {}", table);
+    println!("TRACER seed: {}", table.seed);
+    interrupt::install();
     game::play_tourney(&config, &mut table);
+    exports.finish(&table);
     println!("Ready.");
 }
+
+fn resume(checkpoint_file: &str, exports: &Exports) {
+    let (mut table, config) = match checkpoint::load(checkpoint_file) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            println!("could not resume from {}: {}", checkpoint_file, e);
+            return;
+        }
+    };
+    if !exports.attach(&mut table) {
+        return;
+    }
+
+    println!("TRACER resuming after game {} of {}", table.games_played, config.num_games);
+    interrupt::install();
+    game::play_tourney(&config, &mut table);
+    exports.finish(&table);
+    println!("Ready.");
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().collect();
+    let exports = Exports::from_args(&mut args);
+
+    match args.get(1).map(|s| s.as_str()) {
+        Some("serve") if args.len() > 2 => {
+            let address = args.get(3).map(|s| s.as_str()).unwrap_or(server::DEFAULT_ADDRESS);
+            server::serve(&args[2], address);
+        }
+        Some("join") if args.len() > 3 => client::join(&args[2], &args[3]),
+        Some("arena") if args.len() > 2 => {
+            let address = args.get(3).map(|s| s.as_str()).unwrap_or(arena::DEFAULT_ADDRESS);
+            arena::serve(&args[2], address);
+        }
+        Some("matrix") if args.len() > 2 => matrix::play(&args[2]),
+        Some("tournament") if args.len() > 2 => tournament::play(&args[2]),
+        Some("analyze") if args.len() > 2 => analyze::play(&args[2]),
+        Some("ratings") if args.len() > 2 => ratings::show(&args[2]),
+        Some("--resume") if args.len() > 2 => resume(&args[2], &exports),
+        Some("http") => http::serve(args.get(2).map(|s| s.as_str()).unwrap_or(http::DEFAULT_ADDRESS)),
+        Some("serve") | Some("join") | Some("arena") | Some("matrix") | Some("tournament") | Some("ratings") | Some("analyze") | Some("--resume") | None => emit_usage(),
+        Some(config_file) => play(config_file, &exports),
+    }
+}
@@ -0,0 +1,281 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+
+use crate::config;
+use crate::config::Config;
+use crate::engine;
+use crate::engine::builtin;
+use crate::game;
+use crate::game::Table;
+use crate::player::Player;
+
+// every pair (and, with "table_sizes": [2, 3], every triple) of strategies plays
+// num_games, split evenly over each rotation of the seats
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatrixConfig {
+    pub strategies: Vec<String>,
+    pub num_games: u32,
+    pub num_cards: u32,
+    #[serde(default = "default_table_sizes")]
+    pub table_sizes: Vec<u32>,
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+fn default_table_sizes() -> Vec<u32> { vec![2] }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+}
+
+impl Standing {
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 { 0.0 } else { self.wins as f64 / self.games as f64 }
+    }
+}
+
+// ahead[a][b] / met[a][b]: how often a outscored b in the games they both played, a tie counting half
+#[derive(Debug)]
+pub struct Matrix {
+    pub names: Vec<String>,
+    pub ahead: Vec<Vec<f64>>,
+    pub met: Vec<Vec<u32>>,
+    pub standings: Vec<Standing>,
+}
+
+impl Matrix {
+    fn new(names: &[String]) -> Matrix {
+        let n = names.len();
+        Matrix {
+            names: names.to_vec(),
+            ahead: vec![vec![0.0; n]; n],
+            met: vec![vec![0; n]; n],
+            standings: names.iter().map(|name| Standing{name: name.clone(), games: 0, wins: 0}).collect(),
+        }
+    }
+
+    pub fn win_rate(&self, a: usize, b: usize) -> Option<f64> {
+        match self.met[a][b] {
+            0 => None,
+            met => Some(self.ahead[a][b] / met as f64),
+        }
+    }
+
+    // best win rate first
+    pub fn ranking(&self) -> Vec<&Standing> {
+        let mut ranking: Vec<&Standing> = self.standings.iter().collect();
+        ranking.sort_by(|a, b| b.win_rate().total_cmp(&a.win_rate()).then(a.name.cmp(&b.name)));
+        ranking
+    }
+
+    // seats holds indexes into names, in seat order
+    fn record(&mut self, seats: &[usize], table: &Table) {
+        for (seat, player) in seats.iter().zip(&table.players) {
+            self.standings[*seat].games += player.player_stats.num_games_played();
+            self.standings[*seat].wins += player.num_games_won();
+        }
+        for (i, a) in table.players.iter().enumerate() {
+            for (j, b) in table.players.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                for (x, y) in a.player_stats.game_points.iter().zip(&b.player_stats.game_points) {
+                    self.met[seats[i]][seats[j]] += 1;
+                    self.ahead[seats[i]][seats[j]] += if x > y { 1.0 } else if x == y { 0.5 } else { 0.0 };
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.names.iter().map(|name| name.len()).max().unwrap_or(0).max(6);
+        write!(f, "{:<width$}", "", width = width)?;
+        for name in &self.names {
+            write!(f, " {:>width$}", name, width = width)?;
+        }
+        writeln!(f)?;
+        for (a, name) in self.names.iter().enumerate() {
+            write!(f, "{:<width$}", name, width = width)?;
+            for b in 0..self.names.len() {
+                let cell = match self.win_rate(a, b) {
+                    Some(rate) => format!("{:.1}%", 100.0 * rate),
+                    None => String::from("-"),
+                };
+                write!(f, " {:>width$}", cell, width = width)?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "\nranking:")?;
+        for (place, standing) in self.ranking().iter().enumerate() {
+            writeln!(f, "  {}. {} won {} of {} ({:.1}%)", place + 1, standing.name, standing.wins, standing.games, 100.0 * standing.win_rate())?;
+        }
+        Ok(())
+    }
+}
+
+pub fn build_from_json(config_file: &str) -> Result<MatrixConfig, String> {
+    let data = fs::read_to_string(config_file).map_err(|e| format!("unable to read {}: {}", config_file, e))?;
+    build_from_str(&data)
+}
+
+pub fn build_from_str(data: &str) -> Result<MatrixConfig, String> {
+    let matrix_config: MatrixConfig = serde_json::from_str(data).map_err(|e| e.to_string())?;
+    validate(&matrix_config)?;
+    Ok(matrix_config)
+}
+
+// results are credited by strategy name, so each one may appear only once
+fn validate(matrix_config: &MatrixConfig) -> Result<(), String> {
+    let strategies = &matrix_config.strategies;
+    if strategies.len() < 2 {
+        return Err(String::from("at least two strategies are required"));
+    }
+    if matrix_config.num_games == 0 {
+        return Err(String::from("num_games must be at least 1"));
+    }
+    for (i, name) in strategies.iter().enumerate() {
+        if !builtin::NAMES.contains(&name.as_str()) {
+            return Err(format!("unknown strategy {:?}", name));
+        }
+        if strategies[..i].contains(name) {
+            return Err(format!("strategy {:?} appears more than once", name));
+        }
+    }
+    if matrix_config.table_sizes.is_empty() {
+        return Err(String::from("at least one table size is required"));
+    }
+    for table_size in &matrix_config.table_sizes {
+        if *table_size < 2 || *table_size as usize > strategies.len() {
+            return Err(format!("table_sizes must be from 2 to {}, the number of strategies, not {}", strategies.len(), table_size));
+        }
+    }
+    let largest = matrix_config.table_sizes.iter().copied().max().unwrap_or(2) as usize;
+    config::check_num_cards(matrix_config.num_cards, largest)
+}
+
+// every way to choose k of 0..n, in order
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![vec![]];
+    }
+    let mut result = vec![];
+    for first in 0..n {
+        for rest in combinations(n - first - 1, k - 1) {
+            let mut combination = vec![first];
+            combination.extend(rest.iter().map(|i| i + first + 1));
+            result.push(combination);
+        }
+    }
+    result
+}
+
+// every table plays quietly; only the matrix is printed
+pub fn run(matrix_config: &MatrixConfig) -> Result<Matrix, String> {
+    validate(matrix_config)?;
+    let names = &matrix_config.strategies;
+    let mut matrix = Matrix::new(names);
+    let mut num_tables = 0;
+
+    for table_size in &matrix_config.table_sizes {
+        let size = *table_size as usize;
+        for combination in combinations(names.len(), size) {
+            for rotation in 0..size {
+                let num_games = matrix_config.num_games / *table_size + u32::from((rotation as u32) < matrix_config.num_games % *table_size);
+                if num_games == 0 {
+                    continue;
+                }
+                let mut seats = combination.clone();
+                seats.rotate_left(rotation);
+
+                let players = seats.iter().map(|i| Player::new_from_json(&names[*i], &names[*i])).collect();
+                let seed = match matrix_config.seed {
+                    Some(seed) => engine::game_seed(seed, num_tables),
+                    None => rand::random(),
+                };
+                let config = Config::new(*table_size, num_games, matrix_config.num_cards);
+                let mut table = Table{players, seed, .. Table::new()};
+                game::run_quiet_tourney(&config, &mut table);
+                matrix.record(&seats, &table);
+                num_tables += 1;
+            }
+        }
+    }
+
+    Ok(matrix)
+}
+
+pub fn play(config_file: &str) {
+    match build_from_json(config_file) {
+        Ok(matrix_config) => match run(&matrix_config) {
+            Ok(matrix) => println!("\n\nmatrix complete. win rates, row against column:\n{}", matrix),
+            Err(e) => println!("matrix cannot start: {}", e),
+        },
+        Err(e) => println!("matrix cannot start: {}", e),
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combinations_basic() {
+        // test
+        let result = combinations(4, 3);
+
+        assert_eq!(result, vec![vec![0,1,2], vec![0,1,3], vec![0,2,3], vec![1,2,3]]);
+    }
+
+    #[test]
+    fn test_run_pairs() {
+        let matrix_config = MatrixConfig{
+            strategies: vec![String::from("max_card"), String::from("min_card"), String::from("hybrid_card")],
+            num_games: 5,
+            num_cards: 12,
+            table_sizes: vec![2, 3],
+            seed: Some(17),
+        };
+
+        // test
+        let result = run(&matrix_config).unwrap();
+
+        // each strategy is in 2 pairs of 5 games and a triple of 5 games
+        assert!(result.standings.iter().all(|s| s.games == 15));
+        assert_eq!(result.standings.iter().map(|s| s.wins).sum::<u32>(), 5 * 3 + 5);
+        assert_eq!(result.met[0][1], 10);
+        assert_eq!(result.win_rate(0, 0), None);
+        let max_vs_min = result.win_rate(0, 1).unwrap() + result.win_rate(1, 0).unwrap();
+        assert!((max_vs_min - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_build_from_str_duplicate_strategy() {
+        let data = r#"{"strategies": ["max_card", "min_card", "max_card"], "num_games": 10, "num_cards": 12}"#;
+
+        // test
+        let result = build_from_str(data);
+
+        assert_eq!(result.err(), Some(String::from("strategy \"max_card\" appears more than once")));
+    }
+
+    #[test]
+    fn test_build_from_str_errors() {
+        let unknown = r#"{"strategies": ["max_card", "max_crad"], "num_games": 10, "num_cards": 12}"#;
+        let few_cards = r#"{"strategies": ["max_card", "min_card", "next_card"], "num_games": 10, "num_cards": 3, "table_sizes": [3]}"#;
+
+        // test
+        assert_eq!(build_from_str(unknown).err(), Some(String::from("unknown strategy \"max_crad\"")));
+        assert!(build_from_str(few_cards).unwrap_err().starts_with("num_cards must be at least 4"));
+        assert!(build_from_str("{").is_err());
+        assert!(build_from_str(r#"{"strategies": ["max_card"], "num_games": 10, "num_cards": 12}"#).is_err());
+        let too_big = r#"{"strategies": ["max_card", "min_card"], "num_games": 10, "num_cards": 12, "table_sizes": [2, 3]}"#;
+        assert_eq!(build_from_str(too_big).err(), Some(String::from("table_sizes must be from 2 to 2, the number of strategies, not 3")));
+    }
+}
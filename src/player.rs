@@ -0,0 +1,260 @@
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::engine::builtin;
+use crate::strategy::{Bidder, Strategy};
+use crate::strategy::console::ScoreLine;
+use crate::strategy::external::{ExternalBot, Offer, Score};
+pub use crate::engine::{Hand, IllegalBid, FORFEIT_OFFER};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Player {
+    pub(crate) name: String,
+    pub(crate) strategy: Strategy,
+    pub(crate) player_stats: PlayerStats,
+}
+
+impl Player {
+
+    // this is used by tests
+    #[allow(dead_code)]
+    pub fn new() -> Player {
+        Player {
+            name: String::from("unknown"),
+            strategy: Strategy::new("next_card"),
+            player_stats: PlayerStats::new(),
+        }
+    }
+
+    // for a built-in strategy only; a config's players go through config::build_from_str
+    pub fn new_from_json(name: &str, strategy_name: &str) -> Player {
+        if !builtin::NAMES.contains(&strategy_name) {
+            panic!("{} has an unknown strategy {:?}", name, strategy_name);
+        }
+        Player::new_with_strategy(name, Strategy::new(strategy_name))
+    }
+
+    pub fn new_with_strategy(name: &str, strategy: Strategy) -> Player {
+        Player {
+            name: String::from(name),
+            strategy,
+            player_stats: PlayerStats::new(),
+        }
+    }
+
+    // asks the strategy for a card; whether it may be played is up to the table's GameState
+    pub fn get_bid(&mut self, prize_card: u32, hand: &Hand, max_card: u32) -> Option<u32> {
+        self.strategy.select_card(prize_card, hand, max_card)
+    }
+
+    pub fn is_disqualified(&self) -> bool { self.player_stats.disqualified }
+    pub fn has_resigned(&self) -> bool { self.strategy.has_resigned() }
+    pub fn is_human(&self) -> bool { self.strategy.is_console() }
+    pub fn name(&self) -> &str { &self.name }
+    pub fn strategy_name(&self) -> &str { &self.strategy.name }
+    pub fn timeout_ms(&self) -> u64 { self.strategy.timeout_ms }
+    pub fn num_games_won(&self) -> u32 { self.player_stats.num_games_won }
+    pub fn player_stats(&self) -> &PlayerStats { &self.player_stats }
+    pub fn awaits_connection(&self) -> bool { self.strategy.awaits_connection() }
+    pub fn take_seat(&mut self, bot: ExternalBot) { self.strategy.take_seat(bot); }
+    pub fn start(&mut self) -> Result<(), String> { self.strategy.start() }
+    pub fn set_hot_seat(&mut self, hot_seat: bool) { self.strategy.set_hot_seat(hot_seat); }
+
+    pub fn reveal_prize(&mut self, prize_card: u32, hand: &Hand, max_card: u32) {
+        self.strategy.reveal_prize(prize_card, hand, max_card);
+    }
+
+    pub fn begin_game(&mut self, num_players: u32, num_cards: u32, hand: &Hand) {
+        self.strategy.game_start(&self.name, num_players, num_cards, hand);
+    }
+
+    pub fn end_round(&mut self, prize_card: u32, bids: &[Offer], winner: &str) {
+        self.strategy.round_result(prize_card, bids, winner);
+    }
+
+    pub fn end_game(&mut self, winner: &str, scores: &[Score]) {
+        self.strategy.game_over(winner, scores);
+    }
+
+    pub fn show_scoreboard(&mut self, scoreboard: &[ScoreLine]) {
+        self.strategy.show_scoreboard(scoreboard);
+    }
+
+    pub fn end_tourney(&mut self, winner: &str, scores: &[Score]) {
+        self.strategy.tourney_over(winner, scores);
+    }
+
+    pub fn leave_table(&mut self) { self.strategy.shutdown(); }
+
+    // hands back a remote seat's connection instead of closing it
+    pub fn leave_seat(&mut self) -> Option<ExternalBot> { self.strategy.leave_seat() }
+
+    // a tournament counts each of a player's tables on its own
+    pub fn reset_stats(&mut self) { self.player_stats = PlayerStats::new(); }
+
+    pub fn bids_illegally(&mut self) { self.player_stats.bids_illegally(); }
+    pub fn disqualify(&mut self) { self.player_stats.disqualified = true; }
+    pub fn wins_round(&mut self, prize_card: u32) { self.player_stats.wins_round(prize_card); }
+    pub fn wins_game(&mut self) { self.player_stats.wins_game(); }
+    pub fn loses_game(&mut self) { self.player_stats.loses_game(); }
+    pub fn finishes_game(&mut self, seat: u32, place: u32) {
+        self.player_stats.game_seats.push(seat);
+        self.player_stats.game_placements.push(place);
+    }
+}
+
+impl Default for Player {
+    fn default() -> Player { Player::new() }
+}
+
+impl fmt::Display for Player {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.name, self.player_stats)
+    }
+}
+
+// what happens to a player who makes an illegal bid
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Penalty {
+    #[default]
+    ForfeitRound,
+    PlayLowest,
+    // forfeits every round left in the game and cannot win it
+    Disqualify,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub total_for_game: u32,
+    pub num_games_won: u32,
+    pub num_rounds_won: u32,
+    pub num_illegal_bids: u32,
+    pub disqualified: bool,
+    // the points and rounds won in each finished game, for the report
+    #[serde(default)]
+    pub game_points: Vec<u32>,
+    #[serde(default)]
+    pub game_rounds_won: Vec<u32>,
+    // the place, from 1, in each finished game
+    #[serde(default)]
+    pub game_placements: Vec<u32>,
+    // the seat, from 0, in each finished game
+    #[serde(default)]
+    pub game_seats: Vec<u32>,
+}
+
+impl PlayerStats {
+    fn new() -> PlayerStats {
+        PlayerStats {
+            total_for_game: 0,
+            num_games_won: 0,
+            num_rounds_won: 0,
+            num_illegal_bids: 0,
+            disqualified: false,
+            game_points: vec![],
+            game_rounds_won: vec![],
+            game_placements: vec![],
+            game_seats: vec![],
+        }
+    }
+
+    fn bids_illegally(&mut self) {
+        self.num_illegal_bids += 1;
+    }
+
+    fn wins_round(&mut self, prize_card: u32) {
+        self.total_for_game += prize_card;
+        self.num_rounds_won += 1;
+    }
+
+    fn wins_game(&mut self) {
+        self.ends_game();
+        self.num_games_won += 1;
+    }
+
+    fn loses_game(&mut self) {
+        self.ends_game();
+    }
+
+    fn ends_game(&mut self) {
+        self.game_points.push(self.total_for_game);
+        self.game_rounds_won.push(self.num_rounds_won);
+        self.total_for_game = 0;
+        self.num_rounds_won = 0;
+        self.disqualified = false;
+    }
+
+    pub fn num_games_played(&self) -> u32 { self.game_points.len() as u32 }
+
+    // finishes()[0] is the number of 1st places, finishes()[1] of 2nd places, and so on
+    pub fn finishes(&self) -> Vec<u32> {
+        let worst = self.game_placements.iter().max().copied().unwrap_or(0) as usize;
+        let mut finishes = vec![0; worst];
+        for place in &self.game_placements {
+            finishes[*place as usize - 1] += 1;
+        }
+        finishes
+    }
+
+    pub fn average_placement(&self) -> f64 {
+        if self.game_placements.is_empty() {
+            return 0.0;
+        }
+        self.game_placements.iter().sum::<u32>() as f64 / self.game_placements.len() as f64
+    }
+}
+
+impl fmt::Display for PlayerStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(g: {}, r: {}, t: {}, i: {})", self.num_games_won, self.num_rounds_won, self.total_for_game, self.num_illegal_bids)?;
+        if self.disqualified {
+            write!(f, " DISQUALIFIED")?;
+        }
+        Ok(())
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canary() {
+        let a = 5;
+        let b = 5;
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    #[should_panic(expected = "bach has an unknown strategy \"next_crad\"")]
+    fn test_new_from_json_unknown_strategy() {
+        // test
+        Player::new_from_json("bach", "next_crad");
+    }
+
+    #[test]
+    fn test_finishes_basic() {
+        let stats = PlayerStats{game_placements: vec![1, 3, 1, 2, 3, 3], .. PlayerStats::new()};
+
+        // test
+        let result = stats.finishes();
+
+        assert_eq!(result, vec![2, 1, 3]);
+        assert!((stats.average_placement() - 13.0 / 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_disqualified_for_one_game() {
+        let mut p = Player::new();
+        p.bids_illegally();
+        p.disqualify();
+
+        // test
+        p.loses_game();
+
+        assert!(!p.is_disqualified());
+        assert_eq!(p.player_stats.num_illegal_bids, 1);
+    }
+}
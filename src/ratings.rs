@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::player::Player;
+
+const INITIAL_RATING: f64 = 1500.0;
+const DEFAULT_K: f64 = 32.0;
+
+// "ratings": {"file": "ratings.json", "by": "strategy", "k": 32} in a config; refused over http, as it writes a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RatingsConfig {
+    pub file: String,
+    #[serde(default)]
+    pub by: RateBy,
+    // how far one game moves a rating
+    #[serde(default = "default_k")]
+    pub k: f64,
+}
+
+fn default_k() -> f64 { DEFAULT_K }
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateBy {
+    #[default]
+    Player,
+    Strategy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rating {
+    pub rating: f64,
+    pub games: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Rating { Rating{rating: INITIAL_RATING, games: 0} }
+}
+
+// multi-player Elo: each game counts as a match against every other seat, won by the better placement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ratings {
+    pub config: RatingsConfig,
+    pub ratings: BTreeMap<String, Rating>,
+}
+
+impl Ratings {
+    // starts from the ratings file if there is one
+    pub fn load(config: RatingsConfig) -> Result<Ratings, String> {
+        let ratings = if Path::new(&config.file).exists() {
+            let data = fs::read_to_string(&config.file).map_err(|e| e.to_string())?;
+            serde_json::from_str(&data).map_err(|e| format!("{}: {}", config.file, e))?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Ratings{config, ratings})
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let data = serde_json::to_string_pretty(&self.ratings)?;
+        let scratch = format!("{}.tmp", self.config.file);
+        fs::write(&scratch, data)?;
+        fs::rename(&scratch, &self.config.file)
+    }
+
+    // every external bot and remote seat is its own strategy, known by its command or player
+    fn key(&self, player: &Player) -> String {
+        match (self.config.by, player.strategy.command()) {
+            (RateBy::Player, _) => String::from(player.name()),
+            (RateBy::Strategy, Some(command)) => command.join(" "),
+            (RateBy::Strategy, None) if player.strategy.is_remote() => String::from(player.name()),
+            (RateBy::Strategy, None) => String::from(player.strategy_name()),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Rating {
+        self.ratings.get(key).copied().unwrap_or_default()
+    }
+
+    // placements are 1 for first, with ties sharing a place; one per seat.
+    // seats with the same key are not matched against each other, and a key
+    // seated more than once moves by the average of its seats, as one game
+    pub fn rate(&mut self, keys: &[&str], placements: &[u32]) {
+        let before: Vec<f64> = keys.iter().map(|key| self.get(key).rating).collect();
+        let mut deltas: BTreeMap<&str, (f64, u32)> = BTreeMap::new();
+        for (i, key) in keys.iter().enumerate() {
+            let mut delta = 0.0;
+            let mut num_opponents = 0;
+            for j in 0..keys.len() {
+                if keys[j] == *key {
+                    continue;
+                }
+                let expected = 1.0 / (1.0 + 10f64.powf((before[j] - before[i]) / 400.0));
+                let actual = if placements[i] < placements[j] { 1.0 } else if placements[i] == placements[j] { 0.5 } else { 0.0 };
+                delta += actual - expected;
+                num_opponents += 1;
+            }
+            if num_opponents > 0 {
+                let entry = deltas.entry(*key).or_insert((0.0, 0));
+                entry.0 += delta / num_opponents as f64;
+                entry.1 += 1;
+            }
+        }
+        for (key, (delta, num_seats)) in deltas {
+            let rating = self.ratings.entry(String::from(key)).or_default();
+            rating.rating += self.config.k * delta / num_seats as f64;
+            rating.games += 1;
+        }
+    }
+
+    // after update_game_winner, when each player's place in the game is the last in their stats
+    pub fn rate_game(&mut self, players: &[Player]) {
+        let placements: Vec<u32> = players.iter().map(|p| p.player_stats.game_placements.last().copied().unwrap_or(1)).collect();
+        let keys: Vec<String> = players.iter().map(|p| self.key(p)).collect();
+        let keys: Vec<&str> = keys.iter().map(|key| key.as_str()).collect();
+        self.rate(&keys, &placements);
+    }
+
+    pub fn leaderboard(&self) -> Leaderboard<'_> {
+        let mut entries: Vec<(&str, Rating)> = self.ratings.iter().map(|(key, rating)| (key.as_str(), *rating)).collect();
+        entries.sort_by(|a, b| b.1.rating.total_cmp(&a.1.rating).then(a.0.cmp(b.0)));
+        Leaderboard(entries)
+    }
+}
+
+pub struct Leaderboard<'a>(pub Vec<(&'a str, Rating)>);
+
+impl fmt::Display for Leaderboard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (place, (key, rating)) in self.0.iter().enumerate() {
+            writeln!(f, "  {:>3}. {} {:.1} ({} games)", place + 1, key, rating.rating, rating.games)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn show(ratings_file: &str) {
+    let config = RatingsConfig{file: String::from(ratings_file), by: RateBy::default(), k: DEFAULT_K};
+    match Ratings::load(config) {
+        Ok(ratings) => print!("leaderboard:\n{}", ratings.leaderboard()),
+        Err(e) => println!("could not read ratings: {}", e),
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    fn ratings(file: &str) -> Ratings {
+        Ratings{config: RatingsConfig{file: String::from(file), by: RateBy::Player, k: DEFAULT_K}, ratings: BTreeMap::new()}
+    }
+
+    #[test]
+    fn test_rate_basic() {
+        let mut ratings = ratings("unused.json");
+
+        // test
+        ratings.rate(&["bach", "liszt", "chopin"], &[1, 2, 3]);
+
+        assert!((ratings.get("bach").rating - 1516.0).abs() < 1e-9);
+        assert!((ratings.get("liszt").rating - 1500.0).abs() < 1e-9);
+        assert!((ratings.get("chopin").rating - 1484.0).abs() < 1e-9);
+        assert_eq!(ratings.get("bach").games, 1);
+    }
+
+    #[test]
+    fn test_rate_upset_moves_more() {
+        let mut ratings = ratings("unused.json");
+        ratings.ratings.insert(String::from("bach"), Rating{rating: 1700.0, games: 10});
+
+        // test
+        ratings.rate(&["bach", "liszt"], &[2, 1]);
+
+        let gain = ratings.get("liszt").rating - INITIAL_RATING;
+        assert!(gain > DEFAULT_K / 2.0);
+        assert!((ratings.get("bach").rating + gain - 1700.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let file = std::env::temp_dir().join(format!("waro_ratings_{}.json", std::process::id()));
+        let file = file.to_string_lossy();
+        let mut saved = ratings(&file);
+        saved.rate(&["bach", "liszt"], &[1, 1]);
+        saved.save().unwrap();
+
+        // test
+        let result = Ratings::load(saved.config.clone()).unwrap();
+
+        assert_eq!(result.ratings, saved.ratings);
+        let _ = fs::remove_file(&*file);
+    }
+
+    #[test]
+    fn test_rate_same_key_once() {
+        let mut ratings = ratings("unused.json");
+
+        // test
+        ratings.rate(&["max_card", "min_card", "max_card"], &[1, 2, 3]);
+
+        // max_card beat min_card once and lost to it once, which is even
+        assert!((ratings.get("max_card").rating - 1500.0).abs() < 1e-9);
+        assert!((ratings.get("min_card").rating - 1500.0).abs() < 1e-9);
+        assert_eq!(ratings.get("max_card").games, 1);
+        assert_eq!(ratings.get("min_card").games, 1);
+    }
+
+    #[test]
+    fn test_rate_one_key_only() {
+        let mut ratings = ratings("unused.json");
+
+        // test
+        ratings.rate(&["max_card", "max_card"], &[1, 2]);
+
+        assert_eq!(ratings.get("max_card").games, 0);
+    }
+
+    #[test]
+    fn test_key_by_strategy() {
+        let mut ratings = ratings("unused.json");
+        ratings.config.by = RateBy::Strategy;
+        let bot = Player::new_with_strategy("pat", crate::strategy::Strategy::new_remote(1000));
+        // loading does not start the bot, so this one never runs
+        let external: crate::strategy::Strategy = serde_json::from_str(r#"{"name": "external", "command": ["python3", "bots/nearest_card.py"]}"#).unwrap();
+
+        // test
+        let remote_key = ratings.key(&bot);
+        let external_key = ratings.key(&Player::new_with_strategy("ext", external));
+        let built_in_key = ratings.key(&Player::new_from_json("bach", "max_card"));
+
+        assert_eq!(remote_key, "pat");
+        assert_eq!(external_key, "python3 bots/nearest_card.py");
+        assert_eq!(built_in_key, "max_card");
+    }
+}
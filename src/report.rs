@@ -0,0 +1,378 @@
+
+// the end-of-tourney report: win rates with confidence intervals, points per game,
+// and whether one player is really better than another or just lucky
+
+use std::fmt;
+
+use crate::game::Table;
+use crate::player::Player;
+
+// 95% two-sided
+const Z_95: f64 = 1.96;
+// the same for Student's t, by degrees of freedom from 1 to 30
+const T_95: [f64; 30] = [12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+                         2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+                         2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042];
+// fewer games than this prove nothing, however steady the margin
+const MIN_GAMES: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub low: f64,
+    pub high: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Summary {
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+}
+
+#[derive(Debug)]
+pub struct PlayerReport {
+    pub name: String,
+    pub strategy: String,
+    pub games: u32,
+    pub wins: u32,
+    pub win_rate: f64,
+    pub win_interval: Interval,
+    pub points: Summary,
+    pub rounds_won_per_game: f64,
+    pub average_placement: f64,
+    // 1st places, 2nd places, ...
+    pub finishes: Vec<u32>,
+}
+
+// A vs B over the games they both played, from the per-game difference in points
+#[derive(Debug)]
+pub struct Comparison {
+    pub better: String,
+    pub worse: String,
+    pub points_per_game: f64,
+    pub games: u32,
+    pub t: f64,
+}
+
+impl Comparison {
+    pub fn is_significant(&self) -> bool {
+        self.games >= MIN_GAMES && self.t.abs() > t_95(self.games - 1)
+    }
+}
+
+// past the table, the Cornish-Fisher expansion about the normal is good to three places
+fn t_95(degrees_of_freedom: u32) -> f64 {
+    match degrees_of_freedom {
+        0 => f64::INFINITY,
+        df if df as usize <= T_95.len() => T_95[df as usize - 1],
+        df => {
+            let df = df as f64;
+            Z_95 + (Z_95.powi(3) + Z_95) / (4.0 * df)
+                 + (5.0 * Z_95.powi(5) + 16.0 * Z_95.powi(3) + 3.0 * Z_95) / (96.0 * df * df)
+        }
+    }
+}
+
+// how each seat did, whoever sat there
+#[derive(Debug, PartialEq)]
+pub struct SeatReport {
+    pub seat: u32,
+    pub games: u32,
+    // tied 1st places count for every player sharing them
+    pub first_places: u32,
+    pub mean_points: f64,
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub players: Vec<PlayerReport>,
+    pub comparisons: Vec<Comparison>,
+    pub seats: Vec<SeatReport>,
+}
+
+// Wilson score interval for a proportion, which behaves near 0% and 100% where the normal one does not
+pub fn wilson_interval(successes: u32, trials: u32, z: f64) -> Interval {
+    if trials == 0 {
+        return Interval{low: 0.0, high: 1.0};
+    }
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let margin = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+    Interval{low: (center - margin).max(0.0), high: (center + margin).min(1.0)}
+}
+
+pub fn summarize(values: &[u32]) -> Summary {
+    if values.is_empty() {
+        return Summary::default();
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().map(|v| *v as f64).sum::<f64>() / n;
+
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let middle = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) as f64 / 2.0
+    } else {
+        sorted[middle] as f64
+    };
+
+    // sample standard deviation
+    let stddev = if values.len() < 2 {
+        0.0
+    } else {
+        let sum_squares: f64 = values.iter().map(|v| (*v as f64 - mean).powi(2)).sum();
+        (sum_squares / (n - 1.0)).sqrt()
+    };
+
+    Summary{mean, median, stddev}
+}
+
+fn player_report(player: &Player, num_players: usize) -> PlayerReport {
+    let stats = &player.player_stats;
+    let games = stats.num_games_played();
+    let wins = stats.num_games_won;
+    let win_rate = if games == 0 { 0.0 } else { wins as f64 / games as f64 };
+    let rounds_won = summarize(&stats.game_rounds_won).mean;
+    let mut finishes = stats.finishes();
+    finishes.resize(num_players.max(finishes.len()), 0);
+    PlayerReport {
+        name: player.name.clone(),
+        strategy: player.strategy.name.clone(),
+        games,
+        wins,
+        win_rate,
+        win_interval: wilson_interval(wins, games, Z_95),
+        points: summarize(&stats.game_points),
+        rounds_won_per_game: rounds_won,
+        average_placement: stats.average_placement(),
+        finishes,
+    }
+}
+
+// a paired t-test: players at one table play the same deals, so compare them game by game
+pub fn compare(a: &Player, b: &Player) -> Comparison {
+    let differences: Vec<f64> = a.player_stats.game_points.iter()
+        .zip(&b.player_stats.game_points)
+        .map(|(x, y)| *x as f64 - *y as f64)
+        .collect();
+    let n = differences.len() as f64;
+    let mean = if differences.is_empty() { 0.0 } else { differences.iter().sum::<f64>() / n };
+    let variance = if differences.len() < 2 {
+        0.0
+    } else {
+        differences.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / (n - 1.0)
+    };
+    let standard_error = (variance / n).sqrt();
+    // the same difference in every game is as significant as it gets, once there are MIN_GAMES
+    let t = if standard_error > 0.0 {
+        mean / standard_error
+    } else if differences.len() >= 2 && mean != 0.0 {
+        f64::INFINITY
+    } else {
+        0.0
+    };
+
+    let (better, worse) = if mean >= 0.0 { (a, b) } else { (b, a) };
+    Comparison{better: better.name.clone(), worse: worse.name.clone(), points_per_game: mean.abs(),
+               games: differences.len() as u32, t: t.abs()}
+}
+
+pub fn seat_reports(players: &[Player]) -> Vec<SeatReport> {
+    let mut seats: Vec<SeatReport> = (0..players.len() as u32)
+        .map(|seat| SeatReport{seat, games: 0, first_places: 0, mean_points: 0.0})
+        .collect();
+    let mut points = vec![0; players.len()];
+    for player in players {
+        let stats = &player.player_stats;
+        for ((seat, place), game_points) in stats.game_seats.iter().zip(&stats.game_placements).zip(&stats.game_points) {
+            let seat = *seat as usize;
+            if seat >= seats.len() {
+                continue;
+            }
+            seats[seat].games += 1;
+            seats[seat].first_places += u32::from(*place == 1);
+            points[seat] += game_points;
+        }
+    }
+    for (seat, total) in seats.iter_mut().zip(points) {
+        if seat.games > 0 {
+            seat.mean_points = total as f64 / seat.games as f64;
+        }
+    }
+    seats
+}
+
+pub fn build(table: &Table) -> Report {
+    let players = &table.players;
+    let mut comparisons = vec![];
+    for (i, a) in players.iter().enumerate() {
+        for b in &players[i + 1..] {
+            comparisons.push(compare(a, b));
+        }
+    }
+    Report{players: players.iter().map(|p| player_report(p, players.len())).collect(), comparisons, seats: seat_reports(players)}
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<16} {:<14} {:>7} {:>7} {:>7} {:>17} {:>7} {:>7} {:>7} {:>7} {:>7}  finishes",
+                 "player", "strategy", "games", "wins", "win %", "95% interval", "mean", "median", "stddev", "rounds", "place")?;
+        for p in &self.players {
+            let interval = format!("{:.1}-{:.1}%", 100.0 * p.win_interval.low, 100.0 * p.win_interval.high);
+            let finishes: Vec<String> = p.finishes.iter().map(|n| n.to_string()).collect();
+            writeln!(f, "{:<16} {:<14} {:>7} {:>7} {:>6.1}% {:>17} {:>7.1} {:>7.1} {:>7.1} {:>7.2} {:>7.2}  {}",
+                     p.name, p.strategy, p.games, p.wins, 100.0 * p.win_rate, interval,
+                     p.points.mean, p.points.median, p.points.stddev, p.rounds_won_per_game, p.average_placement, finishes.join("/"))?;
+        }
+        for s in &self.seats {
+            let rate = if s.games == 0 { 0.0 } else { 100.0 * s.first_places as f64 / s.games as f64 };
+            writeln!(f, "seat {}: 1st in {} of {} games ({:.1}%), {:.1} points per game", s.seat + 1, s.first_places, s.games, rate, s.mean_points)?;
+        }
+        for c in &self.comparisons {
+            let verdict = if c.is_significant() { "significantly better than" } else { "not significantly better than" };
+            writeln!(f, "{} is {} {} (+{:.2} points per game over {} games, t = {:.2})", c.better, verdict, c.worse, c.points_per_game, c.games, c.t)?;
+        }
+        Ok(())
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    fn close(a: f64, b: f64) -> bool { (a - b).abs() < 0.001 }
+
+    #[test]
+    fn test_wilson_interval_basic() {
+        // test
+        let result = wilson_interval(30, 100, Z_95);
+
+        assert!(close(result.low, 0.2189), "{:?}", result);
+        assert!(close(result.high, 0.3958), "{:?}", result);
+    }
+
+    #[test]
+    fn test_wilson_interval_no_wins() {
+        // test
+        let result = wilson_interval(0, 10, Z_95);
+
+        assert_eq!(result.low, 0.0);
+        assert!(close(result.high, 0.2775), "{:?}", result);
+    }
+
+    #[test]
+    fn test_summarize_basic() {
+        // test
+        let result = summarize(&[2, 4, 4, 4, 5, 5, 7, 9]);
+
+        assert!(close(result.mean, 5.0));
+        assert!(close(result.median, 4.5));
+        assert!(close(result.stddev, 2.138));
+    }
+
+    #[allow(dead_code)]
+    fn with_points(name: &str, points: Vec<u32>) -> Player {
+        let mut player = Player::new_from_json(name, "next_card");
+        player.player_stats.game_points = points;
+        player
+    }
+
+    #[test]
+    fn test_seat_reports_basic() {
+        let mut a = with_points("mozart", vec![30, 10]);
+        a.player_stats.game_seats = vec![0, 1];
+        a.player_stats.game_placements = vec![1, 2];
+        let mut b = with_points("salieri", vec![20, 10]);
+        b.player_stats.game_seats = vec![1, 0];
+        b.player_stats.game_placements = vec![2, 2];
+
+        // test
+        let result = seat_reports(&[a, b]);
+
+        assert_eq!(result[0], SeatReport{seat: 0, games: 2, first_places: 1, mean_points: 20.0});
+        assert_eq!(result[1], SeatReport{seat: 1, games: 2, first_places: 0, mean_points: 15.0});
+    }
+
+    #[test]
+    fn test_compare_significant() {
+        let a = with_points("mozart", vec![30, 28, 35, 31, 29, 33, 30, 32, 34, 30]);
+        let b = with_points("salieri", vec![10, 12, 9, 11, 14, 10, 8, 12, 11, 13]);
+
+        // test
+        let result = compare(&b, &a);
+
+        assert_eq!(result.better, "mozart");
+        assert!(result.is_significant());
+    }
+
+    #[test]
+    fn test_compare_noise() {
+        let a = with_points("mozart", vec![20, 10, 25, 15, 20, 10]);
+        let b = with_points("haydn", vec![15, 20, 10, 25, 10, 20]);
+
+        // test
+        let result = compare(&a, &b);
+
+        assert!(!result.is_significant());
+    }
+
+    #[test]
+    fn test_compare_same_margin_every_game() {
+        let a = with_points("mozart", vec![30, 30, 30, 30, 30]);
+        let b = with_points("salieri", vec![20, 20, 20, 20, 20]);
+
+        // test
+        let result = compare(&b, &a);
+
+        assert_eq!(result.better, "mozart");
+        assert!(result.is_significant());
+    }
+
+    #[test]
+    fn test_compare_too_few_games() {
+        let a = with_points("mozart", vec![30, 30]);
+        let b = with_points("salieri", vec![20, 20]);
+
+        // test
+        let result = compare(&a, &b);
+
+        assert_eq!(result.games, 2);
+        assert!(!result.is_significant());
+    }
+
+    #[test]
+    fn test_compare_small_sample_uses_t() {
+        // t = 2.45 over 5 games: past the normal's 1.96, short of t's 2.776 for 4 degrees of freedom
+        let a = with_points("mozart", vec![20, 20, 21, 21, 21]);
+        let b = with_points("salieri", vec![20, 20, 20, 20, 20]);
+
+        // test
+        let result = compare(&a, &b);
+
+        assert!(result.t > Z_95);
+        assert!(!result.is_significant());
+    }
+
+    #[test]
+    fn test_t_95() {
+        // test
+        assert_eq!(t_95(4), 2.776);
+        assert!((t_95(31) - 2.040).abs() < 0.001);
+        assert!((t_95(120) - 1.980).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_compare_identical() {
+        let a = with_points("mozart", vec![30, 20, 10]);
+        let b = with_points("haydn", vec![30, 20, 10]);
+
+        // test
+        let result = compare(&a, &b);
+
+        assert!(!result.is_significant());
+    }
+}
@@ -0,0 +1,155 @@
+
+use serde::Deserialize;
+use std::io::{BufRead, BufReader};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use crate::config;
+use crate::game;
+use crate::player::Player;
+use crate::strategy::external;
+use crate::strategy::external::{ExternalBot, Message};
+
+pub const DEFAULT_ADDRESS: &str = "0.0.0.0:7878";
+const JOIN_TIMEOUT_MS: u64 = 5000;
+
+// the first line a client sends: {"type": "join", "name": "alice"}
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Join { name: String },
+}
+
+pub fn serve(config_file: &str, address: &str) {
+    let (mut table, config) = match config::build_from_json(config_file) {
+        Ok(built) => built,
+        Err(e) => {
+            println!("server cannot start: {}", e);
+            return;
+        }
+    };
+    let listener = TcpListener::bind(address).expect("Unable to listen");
+    tracer!("TRACER serving on {}", listener.local_addr().unwrap());
+
+    seat_remote_players(&listener, &mut table.players);
+    game::play_tourney(&config, &mut table);
+}
+
+// blocks until every "remote" seat has a client
+pub fn seat_remote_players(listener: &TcpListener, players: &mut [Player]) {
+    while players.iter().any(|p| p.awaits_connection()) {
+        let open: Vec<&str> = players.iter().filter(|p| p.awaits_connection()).map(|p| p.name()).collect();
+        tracer!("TRACER waiting for remote players: {:?}", open);
+
+        let stream = match listener.accept() {
+            Ok((stream, _address)) => stream,
+            Err(e) => {
+                tracer!("TRACER accept failed: {}", e);
+                continue;
+            }
+        };
+        match seat_client(stream, players) {
+            Ok(name) => tracer!("TRACER {} is seated", name),
+            Err(e) => tracer!("TRACER client rejected: {}", e),
+        }
+    }
+}
+
+fn seat_client(stream: TcpStream, players: &mut [Player]) -> Result<String, String> {
+    stream.set_read_timeout(Some(Duration::from_millis(JOIN_TIMEOUT_MS))).map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|e| e.to_string())?;
+    reader.get_ref().set_read_timeout(None).map_err(|e| e.to_string())?;
+
+    let ClientMessage::Join{name} = serde_json::from_str(line.trim()).map_err(|_| format!("bad join: {:?}", line))?;
+
+    // a client gets the seat with its name if that is open, else the first open seat
+    let seat = players.iter().position(|p| p.awaits_connection() && p.name() == name)
+                      .or_else(|| players.iter().position(|p| p.awaits_connection()));
+    let seat = match seat {
+        Some(seat) => seat,
+        None => {
+            let _ = external::write_message(reader.get_mut(), &Message::Rejected{reason: "the table is full"});
+            return Err(format!("no open seat for {}", name));
+        }
+    };
+
+    let player = &mut players[seat];
+    let mut bot = ExternalBot::from_socket(reader, player.timeout_ms()).map_err(|e| e.to_string())?;
+    bot.send(&Message::Seated{name: player.name()}).map_err(|e| e.to_string())?;
+    player.take_seat(bot);
+    Ok(String::from(player.name()))
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::io::Write;
+    use std::net::SocketAddr;
+    use std::thread;
+    use crate::config::Config;
+    use crate::game::Table;
+    use crate::strategy::Strategy;
+
+    // bids the first card of its hand, and returns every message it received
+    #[allow(dead_code)]
+    fn test_client(address: SocketAddr, name: &str) -> Vec<Value> {
+        let stream = TcpStream::connect(address).unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        writeln!(writer, r#"{{"type": "join", "name": "{}"}}"#, name).unwrap();
+
+        let mut messages = vec![];
+        let mut hand: Vec<u64> = vec![];
+        for line in BufReader::new(stream).lines() {
+            let message: Value = serde_json::from_str(&line.unwrap()).unwrap();
+            match message["type"].as_str().unwrap() {
+                "deal" | "prize_revealed" => {
+                    hand = message["hand"].as_array().unwrap().iter().map(|c| c.as_u64().unwrap()).collect();
+                }
+                "bid_request" => writeln!(writer, r#"{{"round": {}, "bid": {}}}"#, message["round"], hand[0]).unwrap(),
+                _ => {}
+            }
+            messages.push(message);
+        }
+        messages
+    }
+
+    #[allow(dead_code)]
+    fn of_type<'a>(messages: &'a [Value], kind: &str) -> Vec<&'a Value> {
+        messages.iter().filter(|m| m["type"] == kind).collect()
+    }
+
+    #[test]
+    fn test_serve_remote_players() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let alice = thread::spawn(move || test_client(address, "alice"));
+        let bob = thread::spawn(move || test_client(address, "bob"));
+
+        let players = vec![Player::new_with_strategy("alice", Strategy::new_remote(2000)),
+                           Player::new_with_strategy("bob", Strategy::new_remote(2000)),
+                           Player::new_from_json("bach", "next_card")];
+        let mut table = Table{players, .. Table::new()};
+        const NUM_CARDS: u32 = 12;
+        let config = Config{num_players: 3, num_games: 2, num_cards: NUM_CARDS, num_cards_per_hand: NUM_CARDS / 4};
+
+        // test
+        seat_remote_players(&listener, &mut table.players);
+        game::play_tourney(&config, &mut table);
+
+        let alice = alice.join().unwrap();
+        let bob = bob.join().unwrap();
+        assert_eq!(of_type(&alice, "seated")[0]["name"], "alice");
+        assert_eq!(of_type(&bob, "seated")[0]["name"], "bob");
+        assert_eq!(of_type(&alice, "game_over").len(), 2);
+        assert_eq!(of_type(&alice, "round_result").len(), 6);
+        // each client sees only its own hand
+        let alice_hand = &of_type(&alice, "deal")[0]["hand"];
+        let bob_hand = &of_type(&bob, "deal")[0]["hand"];
+        for card in alice_hand.as_array().unwrap() {
+            assert!(!bob_hand.as_array().unwrap().contains(card));
+        }
+    }
+}
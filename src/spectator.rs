@@ -0,0 +1,262 @@
+
+use serde::Serialize;
+use std::io;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::strategy::external::{Offer, Score};
+
+mod websocket;
+
+// a spectator that stops reading is dropped rather than stalling the feed
+const WRITE_TIMEOUT_MS: u64 = 1000;
+
+// what spectators see, one JSON object per websocket text message
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event<'a> {
+    // the whole table; late joiners get the latest one first
+    Table { prize_card: u32, kitty_size: usize, players: Vec<Seat<'a>> },
+    GameStart { num_players: u32, num_cards: u32 },
+    PrizeRevealed { prize_card: u32 },
+    Bids { prize_card: u32, bids: &'a [Offer] },
+    RoundResult { prize_card: u32, winner: &'a str, scores: &'a [Score] },
+    GameOver { winner: &'a str, scores: &'a [Score] },
+    TourneyOver { winner: &'a str, scores: &'a [Score] },
+}
+
+#[derive(Debug, Serialize)]
+pub struct Seat<'a> {
+    pub name: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hand: Option<&'a [u32]>,
+    pub total_for_game: u32,
+    pub num_rounds_won: u32,
+    pub num_games_won: u32,
+    pub disqualified: bool,
+}
+
+#[derive(Debug)]
+enum Outgoing {
+    Text { due: Instant, text: String, snapshot: bool },
+    Close,
+}
+
+#[derive(Default)]
+struct Spectators {
+    streams: Vec<TcpStream>,
+    snapshot: Option<String>,
+}
+
+// broadcasts events to every connected spectator, optionally after a delay
+#[derive(Debug)]
+pub struct Feed {
+    address: SocketAddr,
+    pub hide_hands: bool,
+    delay: Duration,
+    sender: Sender<Outgoing>,
+    broadcaster: Option<JoinHandle<()>>,
+}
+
+impl Feed {
+    pub fn start(address: &str, hide_hands: bool, delay_ms: u64) -> io::Result<Feed> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+
+        let spectators = Arc::new(Mutex::new(Spectators::default()));
+        let accepting = Arc::clone(&spectators);
+        thread::spawn(move || accept_spectators(listener, accepting));
+
+        let (sender, receiver) = channel();
+        let broadcaster = thread::spawn(move || broadcast(receiver, &spectators));
+
+        let feed = Feed{address, hide_hands, delay: Duration::from_millis(delay_ms), sender, broadcaster: Some(broadcaster)};
+        tracer!("TRACER spectators can watch on ws://{}", feed.address());
+        Ok(feed)
+    }
+
+    pub fn address(&self) -> SocketAddr { self.address }
+
+    pub fn publish(&self, event: &Event) {
+        let text = serde_json::to_string(event).unwrap();
+        let snapshot = matches!(event, Event::Table{..});
+        let _ = self.sender.send(Outgoing::Text{due: Instant::now() + self.delay, text, snapshot});
+    }
+
+    // waits for delayed events to go out, then says goodbye to every spectator
+    pub fn finish(mut self) {
+        let _ = self.sender.send(Outgoing::Close);
+        if let Some(broadcaster) = self.broadcaster.take() {
+            let _ = broadcaster.join();
+        }
+    }
+}
+
+// each spectator's handshake runs on its own thread, so a slow client holds up nobody else
+fn accept_spectators(listener: TcpListener, spectators: Arc<Mutex<Spectators>>) {
+    for stream in listener.incoming().flatten() {
+        let spectators = Arc::clone(&spectators);
+        thread::spawn(move || {
+            if let Err(e) = join(stream, &spectators) {
+                tracer!("TRACER spectator rejected: {}", e);
+            }
+        });
+    }
+}
+
+// the upgrade is answered with the spectators locked, so no event can go out
+// between the 101 and the spectator joining the broadcast
+fn join(mut stream: TcpStream, spectators: &Mutex<Spectators>) -> io::Result<()> {
+    let key = websocket::read_upgrade(&mut stream)?;
+    stream.set_write_timeout(Some(Duration::from_millis(WRITE_TIMEOUT_MS)))?;
+
+    let mut spectators = spectators.lock().unwrap();
+    websocket::write_upgrade(&mut stream, &key)?;
+    if let Some(snapshot) = &spectators.snapshot {
+        websocket::write_text(&mut stream, snapshot)?;
+    }
+    spectators.streams.push(stream);
+    Ok(())
+}
+
+fn broadcast(receiver: Receiver<Outgoing>, spectators: &Mutex<Spectators>) {
+    for outgoing in receiver {
+        match outgoing {
+            Outgoing::Text{due, text, snapshot} => {
+                let now = Instant::now();
+                if due > now {
+                    thread::sleep(due - now);
+                }
+                let mut spectators = spectators.lock().unwrap();
+                if snapshot {
+                    spectators.snapshot = Some(text.clone());
+                }
+                spectators.streams.retain_mut(|stream| websocket::write_text(stream, &text).is_ok());
+            }
+            Outgoing::Close => {
+                let mut spectators = spectators.lock().unwrap();
+                for stream in &mut spectators.streams {
+                    let _ = websocket::write_close(stream);
+                }
+                spectators.streams.clear();
+                return;
+            }
+        }
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    // connects like a browser would and returns the stream after the handshake
+    #[allow(dead_code)]
+    fn spectate(address: SocketAddr) -> TcpStream {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET / HTTP/1.1\r\nHost: test\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+                        Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n").unwrap();
+        // byte by byte, so a frame sent right after the handshake stays unread
+        let mut response = vec![];
+        while !response.ends_with(b"\r\n\r\n") {
+            let mut byte = [0u8];
+            stream.read_exact(&mut byte).unwrap();
+            response.push(byte[0]);
+        }
+        assert!(String::from_utf8_lossy(&response).starts_with("HTTP/1.1 101"));
+        stream
+    }
+
+    // returns None once the server closes the feed
+    #[allow(dead_code)]
+    fn read_message(stream: &mut TcpStream) -> Option<serde_json::Value> {
+        let mut header = [0u8; 2];
+        stream.read_exact(&mut header).unwrap();
+        if header[0] & 0x0f == 0x8 {
+            return None;
+        }
+        let len = match header[1] {
+            126 => {
+                let mut len = [0u8; 2];
+                stream.read_exact(&mut len).unwrap();
+                usize::from(u16::from_be_bytes(len))
+            }
+            127 => {
+                let mut len = [0u8; 8];
+                stream.read_exact(&mut len).unwrap();
+                u64::from_be_bytes(len) as usize
+            }
+            len => usize::from(len),
+        };
+        let mut payload = vec![0; len];
+        stream.read_exact(&mut payload).unwrap();
+        Some(serde_json::from_slice(&payload).unwrap())
+    }
+
+    #[allow(dead_code)]
+    fn table_event(prize_card: u32) -> Event<'static> {
+        Event::Table{prize_card, kitty_size: 3, players: vec![]}
+    }
+
+    #[test]
+    fn test_late_joiner_gets_snapshot() {
+        let feed = Feed::start("127.0.0.1:0", false, 0).unwrap();
+        let mut early = spectate(feed.address());
+        feed.publish(&table_event(7));
+        assert_eq!(read_message(&mut early).unwrap()["prize_card"], 7);
+
+        // test
+        let mut late = spectate(feed.address());
+
+        let snapshot = read_message(&mut late).unwrap();
+        assert_eq!(snapshot["type"], "table");
+        assert_eq!(snapshot["prize_card"], 7);
+        feed.publish(&Event::PrizeRevealed{prize_card: 9});
+        feed.finish();
+        assert_eq!(read_message(&mut late).unwrap()["type"], "prize_revealed");
+        assert!(read_message(&mut late).is_none());
+        assert_eq!(read_message(&mut early).unwrap()["prize_card"], 9);
+    }
+
+    #[test]
+    fn test_delay() {
+        let feed = Feed::start("127.0.0.1:0", false, 200).unwrap();
+        let mut spectator = spectate(feed.address());
+        let start = Instant::now();
+
+        // test
+        feed.publish(&Event::PrizeRevealed{prize_card: 9});
+
+        assert_eq!(read_message(&mut spectator).unwrap()["prize_card"], 9);
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        feed.finish();
+    }
+
+    #[test]
+    fn test_tourney_feed_hides_hands() {
+        let data = r#"{"num_cards": 12, "num_games": 2,
+                       "players": [{"name": "bach", "strategy": "next_card"}, {"name": "liszt", "strategy": "max_card"}],
+                       "spectators": {"address": "127.0.0.1:0", "hide_hands": true}}"#;
+        let (mut table, config) = crate::config::build_from_str(data).unwrap();
+        let mut spectator = spectate(table.feed.as_ref().unwrap().address());
+
+        // test
+        crate::game::play_tourney(&config, &mut table);
+
+        let mut messages = vec![];
+        while let Some(message) = read_message(&mut spectator) {
+            messages.push(message);
+        }
+        let count = |t: &str| messages.iter().filter(|m| m["type"] == t).count();
+        assert_eq!(count("game_start"), 2);
+        assert_eq!(count("bids"), 8);
+        assert_eq!(count("round_result"), 8);
+        assert_eq!(count("game_over"), 2);
+        assert_eq!(count("tourney_over"), 1);
+        assert!(messages.iter().filter(|m| m["type"] == "table").all(|m| m["players"][0]["hand"].is_null()));
+    }
+}
@@ -0,0 +1,104 @@
+
+// just enough of RFC 6455 for a server that only sends text frames
+
+use base64::Engine;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const HANDSHAKE_TIMEOUT_MS: u64 = 5000;
+
+// reads the client's upgrade request and returns its key; a request without one gets a 400
+pub fn read_upgrade(stream: &mut TcpStream) -> io::Result<String> {
+    stream.set_read_timeout(Some(Duration::from_millis(HANDSHAKE_TIMEOUT_MS)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut key = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(String::from(value.trim()));
+            }
+        }
+    }
+    stream.set_read_timeout(None)?;
+
+    match key {
+        Some(key) => Ok(key),
+        None => {
+            stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?;
+            Err(io::Error::new(io::ErrorKind::InvalidData, "not a websocket upgrade"))
+        }
+    }
+}
+
+// after this the stream carries frames
+pub fn write_upgrade(stream: &mut TcpStream, key: &str) -> io::Result<()> {
+    let response = format!("HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                           accept_key(key));
+    stream.write_all(response.as_bytes())?;
+    stream.flush()
+}
+
+pub fn accept_key(key: &str) -> String {
+    let digest = sha1_smol::Sha1::from(format!("{}{}", key, GUID)).digest().bytes();
+    base64::engine::general_purpose::STANDARD.encode(digest)
+}
+
+pub fn write_text<W: Write>(output: &mut W, text: &str) -> io::Result<()> {
+    write_frame(output, 0x1, text.as_bytes())
+}
+
+pub fn write_close<W: Write>(output: &mut W) -> io::Result<()> {
+    write_frame(output, 0x8, &[])
+}
+
+// server frames are never masked
+fn write_frame<W: Write>(output: &mut W, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    let len = payload.len();
+    if len < 126 {
+        frame.push(len as u8);
+    } else if len <= 0xffff {
+        frame.push(126);
+        frame.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        frame.push(127);
+        frame.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+    frame.extend_from_slice(payload);
+    output.write_all(&frame)?;
+    output.flush()
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_key_rfc_example() {
+        // test
+        let result = accept_key("dGhlIHNhbXBsZSBub25jZQ==");
+
+        assert_eq!(result, "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn test_write_text_short() {
+        let mut output = vec![];
+
+        // test
+        write_text(&mut output, "hi").unwrap();
+
+        assert_eq!(output, vec![0x81, 2, b'h', b'i']);
+    }
+}
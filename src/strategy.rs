@@ -0,0 +1,343 @@
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use crate::engine::Hand;
+pub use crate::engine::builtin::{built_in_card, explain};
+
+pub mod console;
+pub mod external;
+
+use console::{Console, ScoreLine};
+use external::{ExternalBot, Offer, Score};
+
+// how a seat bids and hears about the game. the built-in strategies, the console,
+// external bots and remote seats are all Bidders, and so is a strategy written in
+// Rust by a user of the library (see Strategy::new_custom); only select_card is required
+pub trait Bidder: fmt::Debug + Send {
+    // return a card from hand; None (or a card not in hand) is an illegal bid
+    fn select_card(&mut self, prize_card: u32, hand: &Hand, highest_card: u32) -> Option<u32>;
+
+    // every seat sees the prize before any of them is asked to bid
+    fn reveal_prize(&mut self, _prize_card: u32, _hand: &Hand, _highest_card: u32) {}
+    fn game_start(&mut self, _name: &str, _num_players: u32, _num_cards: u32, _hand: &Hand) {}
+    fn round_result(&mut self, _prize_card: u32, _bids: &[Offer], _winner: &str) {}
+    fn game_over(&mut self, _winner: &str, _scores: &[Score]) {}
+    // the table's scores, after every round
+    fn show_scoreboard(&mut self, _scoreboard: &[ScoreLine]) {}
+    fn tourney_over(&mut self, _winner: &str, _scores: &[Score]) {}
+    // true once the seat has given up the rest of the tourney
+    fn has_resigned(&self) -> bool { false }
+    fn shutdown(&mut self) {}
+}
+
+// one of the strategies in engine/builtin.rs, by name
+#[derive(Debug)]
+pub struct BuiltIn {
+    pub name: String,
+}
+
+impl Bidder for BuiltIn {
+    fn select_card(&mut self, prize_card: u32, hand: &Hand, highest_card: u32) -> Option<u32> {
+        built_in_card(&self.name, prize_card, hand, highest_card)
+    }
+}
+
+// what plays the seat
+#[derive(Debug)]
+enum Kind {
+    BuiltIn(BuiltIn),
+    Console(Console),
+    // with its command; not started until Strategy::start when loaded from a checkpoint
+    External(Option<ExternalBot>, Vec<String>),
+    // empty until a client connects, see server.rs
+    Remote(Option<ExternalBot>),
+    Custom(Box<dyn Bidder>),
+}
+
+#[derive(Debug)]
+pub struct Strategy {
+    pub name: String,
+    // per-move limit for an external bot or a remote seat
+    pub timeout_ms: u64,
+    kind: Kind,
+}
+
+pub const CONSOLE: &str = "console";
+pub const EXTERNAL: &str = "external";
+pub const REMOTE: &str = "remote";
+// a seat played over the http api, see http.rs
+pub const API: &str = "api";
+
+impl Strategy {
+    pub fn new(name: &str) -> Strategy {
+        let kind = if name == CONSOLE {
+            Kind::Console(Console::for_terminal())
+        } else {
+            Kind::BuiltIn(BuiltIn{name: String::from(name)})
+        };
+        Strategy{name: String::from(name), timeout_ms: external::DEFAULT_TIMEOUT_MS, kind}
+    }
+
+    pub fn new_custom(name: &str, bidder: Box<dyn Bidder>) -> Strategy {
+        Strategy{name: String::from(name), timeout_ms: external::DEFAULT_TIMEOUT_MS, kind: Kind::Custom(bidder)}
+    }
+
+    // a bad command is a config error, so it is reported rather than a panic
+    pub fn new_external(command: &[String], timeout_ms: u64) -> Result<Strategy, String> {
+        let mut strategy = Strategy{name: String::from(EXTERNAL), timeout_ms, kind: Kind::External(None, command.to_vec())};
+        strategy.start()?;
+        Ok(strategy)
+    }
+
+    // starts an external bot that is not running yet; every other seat is ready as it is
+    pub fn start(&mut self) -> Result<(), String> {
+        if let Kind::External(bot, command) = &mut self.kind {
+            if bot.is_none() {
+                let started = ExternalBot::spawn(command, self.timeout_ms)
+                    .map_err(|e| format!("unable to start external bot {:?}: {}", command, e))?;
+                *bot = Some(started);
+            }
+        }
+        Ok(())
+    }
+
+    // the seat stays empty until a client connects, see server.rs
+    pub fn new_remote(timeout_ms: u64) -> Strategy {
+        Strategy{name: String::from(REMOTE), timeout_ms, kind: Kind::Remote(None)}
+    }
+
+    pub fn awaits_connection(&self) -> bool {
+        matches!(self.kind, Kind::Remote(None))
+    }
+
+    pub fn is_remote(&self) -> bool {
+        matches!(self.kind, Kind::Remote(_))
+    }
+
+    // what an external bot runs, which tells one bot from another
+    pub fn command(&self) -> Option<&[String]> {
+        match &self.kind {
+            Kind::External(_, command) => Some(command),
+            _ => None,
+        }
+    }
+
+    // the connected bot plays the seat from now on, whatever played it before
+    pub fn take_seat(&mut self, bot: ExternalBot) {
+        self.kind = Kind::Remote(Some(bot));
+    }
+
+    // hands back a remote seat's connection instead of closing it
+    pub fn leave_seat(&mut self) -> Option<ExternalBot> {
+        match &mut self.kind {
+            Kind::Remote(seat) => seat.take(),
+            _ => None,
+        }
+    }
+
+    // a coach only makes sense for a human at the console
+    pub fn with_coach(mut self, coach_name: &str) -> Strategy {
+        if let Kind::Console(console) = &mut self.kind {
+            console.coach = Some(String::from(coach_name));
+        }
+        self
+    }
+
+    pub fn set_hot_seat(&mut self, hot_seat: bool) {
+        if let Kind::Console(console) = &mut self.kind {
+            console.hot_seat = hot_seat;
+        }
+    }
+
+    pub fn is_console(&self) -> bool { matches!(self.kind, Kind::Console(_)) }
+
+    // an empty remote seat, or an external bot not started yet, has nobody to bid
+    fn bidder(&self) -> Option<&dyn Bidder> {
+        match &self.kind {
+            Kind::BuiltIn(built_in) => Some(built_in),
+            Kind::Console(console) => Some(console),
+            Kind::External(bot, _) | Kind::Remote(bot) => bot.as_ref().map(|bot| bot as &dyn Bidder),
+            Kind::Custom(bidder) => Some(bidder.as_ref()),
+        }
+    }
+
+    fn bidder_mut(&mut self) -> Option<&mut dyn Bidder> {
+        match &mut self.kind {
+            Kind::BuiltIn(built_in) => Some(built_in),
+            Kind::Console(console) => Some(console),
+            Kind::External(bot, _) | Kind::Remote(bot) => bot.as_mut().map(|bot| bot as &mut dyn Bidder),
+            Kind::Custom(bidder) => Some(bidder.as_mut()),
+        }
+    }
+}
+
+// a strategy passes everything on to whatever plays the seat
+impl Bidder for Strategy {
+    fn select_card(&mut self, prize_card: u32, hand: &Hand, highest_card: u32) -> Option<u32> {
+        self.bidder_mut()?.select_card(prize_card, hand, highest_card)
+    }
+
+    fn reveal_prize(&mut self, prize_card: u32, hand: &Hand, highest_card: u32) {
+        if let Some(bidder) = self.bidder_mut() {
+            bidder.reveal_prize(prize_card, hand, highest_card);
+        }
+    }
+
+    fn game_start(&mut self, name: &str, num_players: u32, num_cards: u32, hand: &Hand) {
+        if let Some(bidder) = self.bidder_mut() {
+            bidder.game_start(name, num_players, num_cards, hand);
+        }
+    }
+
+    fn round_result(&mut self, prize_card: u32, bids: &[Offer], winner: &str) {
+        if let Some(bidder) = self.bidder_mut() {
+            bidder.round_result(prize_card, bids, winner);
+        }
+    }
+
+    fn game_over(&mut self, winner: &str, scores: &[Score]) {
+        if let Some(bidder) = self.bidder_mut() {
+            bidder.game_over(winner, scores);
+        }
+    }
+
+    fn show_scoreboard(&mut self, scoreboard: &[ScoreLine]) {
+        if let Some(bidder) = self.bidder_mut() {
+            bidder.show_scoreboard(scoreboard);
+        }
+    }
+
+    fn tourney_over(&mut self, winner: &str, scores: &[Score]) {
+        if let Some(bidder) = self.bidder_mut() {
+            bidder.tourney_over(winner, scores);
+        }
+    }
+
+    fn has_resigned(&self) -> bool {
+        self.bidder().is_some_and(|bidder| bidder.has_resigned())
+    }
+
+    // a remote seat is left empty
+    fn shutdown(&mut self) {
+        if let Some(bidder) = self.bidder_mut() {
+            bidder.shutdown();
+        }
+        if let Kind::Remote(seat) = &mut self.kind {
+            *seat = None;
+        }
+    }
+}
+
+// a strategy is saved as its configuration, not its state. loading one has no
+// side effects: an external bot comes back not started (see Strategy::start), a
+// remote seat comes back empty, and a custom Bidder cannot be loaded at all
+#[derive(Serialize, Deserialize)]
+struct StrategySpec {
+    name: String,
+    // remote and custom seats can have any name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kind: Option<SavedKind>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    command: Vec<String>,
+    #[serde(default = "external::default_timeout_ms")]
+    timeout_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    coach: Option<String>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SavedKind {
+    Remote,
+    Custom,
+}
+
+impl Serialize for Strategy {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (kind, command, coach) = match &self.kind {
+            Kind::External(_, command) => (None, command.clone(), None),
+            Kind::Console(console) => (None, vec![], console.coach.clone()),
+            Kind::Remote(_) => (Some(SavedKind::Remote), vec![], None),
+            Kind::Custom(_) => (Some(SavedKind::Custom), vec![], None),
+            Kind::BuiltIn(_) => (None, vec![], None),
+        };
+        let spec = StrategySpec{name: self.name.clone(), kind, command, timeout_ms: self.timeout_ms, coach};
+        spec.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Strategy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Strategy, D::Error> {
+        let spec = StrategySpec::deserialize(deserializer)?;
+        let strategy = match (spec.kind, spec.name.as_str()) {
+            (Some(SavedKind::Custom), name) => {
+                return Err(serde::de::Error::custom(format!("the custom strategy {:?} cannot be loaded", name)));
+            }
+            (Some(SavedKind::Remote), _) | (None, REMOTE) => Strategy::new_remote(spec.timeout_ms),
+            (None, EXTERNAL) => Strategy{name: String::new(), timeout_ms: spec.timeout_ms, kind: Kind::External(None, spec.command)},
+            (None, name) => Strategy{timeout_ms: spec.timeout_ms, .. Strategy::new(name)},
+        };
+        let strategy = Strategy{name: spec.name, .. strategy};
+        Ok(match &spec.coach {
+            Some(coach) => strategy.with_coach(coach),
+            None => strategy,
+        })
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    // bids the card one above the prize if it has it
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct OneUp;
+
+    impl Bidder for OneUp {
+        fn select_card(&mut self, prize_card: u32, hand: &Hand, _highest_card: u32) -> Option<u32> {
+            hand.cards.iter().copied().find(|card| *card == prize_card + 1)
+        }
+    }
+
+    #[test]
+    fn test_custom_bidder() {
+        let mut strategy = Strategy::new_custom("one_up", Box::new(OneUp));
+        let hand = Hand{cards: vec![4,2,6,8]};
+
+        // test
+        let result = (strategy.select_card(5, &hand, 12), strategy.select_card(9, &hand, 12));
+
+        assert_eq!(result, (Some(6), None));
+    }
+
+    // bids its first card and counts the rounds it is told about
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct Counter(std::sync::Arc<std::sync::atomic::AtomicU32>);
+
+    impl Bidder for Counter {
+        fn select_card(&mut self, _prize_card: u32, hand: &Hand, _highest_card: u32) -> Option<u32> {
+            hand.cards.first().copied()
+        }
+
+        fn round_result(&mut self, _prize_card: u32, _bids: &[Offer], _winner: &str) {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_custom_bidder_hears_every_round() {
+        let data = r#"{"num_cards": 12, "num_games": 2,
+                       "players": [{"name": "bach", "strategy": "next_card"}, {"name": "liszt", "strategy": "max_card"}]}"#;
+        let (mut table, config) = crate::config::build_from_str(data).unwrap();
+        let rounds = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+        table.players[0].strategy = Strategy::new_custom("counter", Box::new(Counter(std::sync::Arc::clone(&rounds))));
+
+        // test
+        crate::game::play_tourney(&config, &mut table);
+
+        assert_eq!(rounds.load(std::sync::atomic::Ordering::SeqCst), 8);
+    }
+}
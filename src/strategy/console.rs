@@ -0,0 +1,536 @@
+
+use std::io;
+use std::io::{BufRead, IsTerminal, Write};
+
+use super::external::{Offer, Score};
+use crate::engine::Hand;
+use crate::trace;
+use super::{built_in_card, explain, Bidder};
+
+pub mod tui;
+
+const HELP: &str = "type a card from your hand to bid, or one of:
+  hand     show your hand
+  history  show the rounds played so far this game
+  scores   show the points won so far this game
+  help     show this message
+  quit     forfeit the rest of the tourney";
+
+#[derive(Debug)]
+pub struct RoundRecord {
+    pub prize_card: u32,
+    pub bids: Vec<Offer>,
+    pub winner: String,
+    pub advice: Option<u32>,
+}
+
+// one player's line on the scoreboard, as the table keeps it in PlayerStats
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreLine {
+    pub name: String,
+    pub points: u32,
+    pub rounds_won: u32,
+    pub games_won: u32,
+}
+
+// a round where the human ignored the coach. points is what the coach's
+// card would have changed, holding the other bids fixed: positive if
+// following the coach would have won the prize, negative if it would have lost it
+#[derive(Debug, PartialEq)]
+pub struct Lesson {
+    pub round: usize,
+    pub prize_card: u32,
+    pub bid: u32,
+    pub advice: u32,
+    pub points: i64,
+}
+
+// state for a human at the terminal
+#[derive(Debug)]
+pub struct Console {
+    pub name: String,
+    pub history: Vec<RoundRecord>,
+    pub resigned: bool,
+    pub coach: Option<String>,
+    pub hot_seat: bool,
+    // full-screen picker instead of typed input, see tui.rs
+    pub tui: bool,
+    // in seat order, sent by the table after every round
+    pub scoreboard: Vec<ScoreLine>,
+    advice: Option<u32>,
+    // the TRACER lines this console turned off while its screen is up
+    muted_trace: bool,
+}
+
+impl Console {
+    pub fn new() -> Console {
+        Console {
+            name: String::new(),
+            history: vec![],
+            resigned: false,
+            coach: None,
+            hot_seat: false,
+            tui: false,
+            scoreboard: vec![],
+            advice: None,
+            muted_trace: false,
+        }
+    }
+
+    // the full-screen picker is only used when a person is at both ends
+    pub fn for_terminal() -> Console {
+        Console{tui: tui::available(), .. Console::new()}
+    }
+
+    // from the first game until the tourney is over the screen is the picker's alone
+    fn mute_trace(&mut self) {
+        if self.tui && !self.muted_trace && trace::enabled() {
+            trace::set_enabled(false);
+            self.muted_trace = true;
+        }
+    }
+
+    fn restore_trace(&mut self) {
+        if self.muted_trace {
+            trace::set_enabled(true);
+            self.muted_trace = false;
+        }
+    }
+
+    // returns None once the player quits or input runs out
+    pub fn prompt<R: BufRead, W: Write>(&mut self, prize_card: u32, hand: &Hand, highest_card: u32,
+                                        input: &mut R, output: &mut W) -> Option<u32> {
+        if self.resigned {
+            return None;
+        }
+
+        if self.hot_seat && !self.pass_keyboard(input, output) {
+            return None;
+        }
+        let selection = if self.tui {
+            tui::choose(self, prize_card, hand, highest_card, input, output)
+        } else {
+            self.read_pick(prize_card, hand, highest_card, input, output)
+        };
+        if self.hot_seat {
+            clear_screen(output);
+            let _ = writeln!(output, "{} has bid.", self.name);
+        }
+        selection
+    }
+
+    // hides the previous player's screen until the next player is at the keyboard
+    fn pass_keyboard<R: BufRead, W: Write>(&mut self, input: &mut R, output: &mut W) -> bool {
+        clear_screen(output);
+        let _ = writeln!(output, "pass the keyboard to {}, then press Enter", self.name);
+        let _ = output.flush();
+
+        let mut line = String::new();
+        match input.read_line(&mut line) {
+            Ok(0) | Err(_) => {
+                let _ = writeln!(output, "no more input: forfeiting the rest of the tourney");
+                self.resigned = true;
+                false
+            }
+            Ok(_) => {
+                clear_screen(output);
+                true
+            }
+        }
+    }
+
+    fn read_pick<R: BufRead, W: Write>(&mut self, prize_card: u32, hand: &Hand, highest_card: u32,
+                                       input: &mut R, output: &mut W) -> Option<u32> {
+        if self.hot_seat {
+            let _ = writeln!(output, "{}:", self.name);
+        }
+        let _ = writeln!(output, "\nprize_card: {}", prize_card);
+        let _ = writeln!(output, "your hand: {}", hand);
+
+        if let Some(advice) = self.take_advice(prize_card, hand, highest_card) {
+            let _ = writeln!(output, "{}", advice);
+        }
+
+        loop {
+            let _ = writeln!(output, "enter your pick (or 'help'):");
+            let _ = output.flush();
+
+            let mut pick = String::new();
+            match input.read_line(&mut pick) {
+                Ok(0) | Err(_) => {
+                    let _ = writeln!(output, "no more input: forfeiting the rest of the tourney");
+                    self.resigned = true;
+                    return None;
+                }
+                Ok(_) => {}
+            }
+
+            match pick.trim() {
+                "" => {}
+                "hand" => { let _ = writeln!(output, "your hand: {}", hand); }
+                "history" => self.emit_history(output),
+                "scores" => self.emit_scores(output),
+                "help" => { let _ = writeln!(output, "{}", HELP); }
+                "quit" => {
+                    let _ = writeln!(output, "forfeiting the rest of the tourney");
+                    self.resigned = true;
+                    return None;
+                }
+                _ => match validate_pick(&pick, hand) {
+                    Some(card) => return Some(card),
+                    None => { let _ = writeln!(output, "'{}' is not a card in your hand", pick.trim()); }
+                },
+            }
+        }
+    }
+
+    // asks the coach, if any, and remembers its card for the review
+    fn take_advice(&mut self, prize_card: u32, hand: &Hand, highest_card: u32) -> Option<String> {
+        self.advice = None;
+        let coach = self.coach.as_ref()?;
+        let advice = built_in_card(coach, prize_card, hand, highest_card)?;
+        let line = format!("coach ({}) bids {}: {}", coach, advice, explain(coach, prize_card, highest_card));
+        self.advice = Some(advice);
+        Some(line)
+    }
+
+    pub fn lessons(&self) -> Vec<Lesson> {
+        let mut lessons = vec![];
+        for (index, record) in self.history.iter().enumerate() {
+            let bid = record.bids.iter().find(|b| b.name == self.name).map(|b| b.offer);
+            let (bid, advice) = match (bid, record.advice) {
+                (Some(bid), Some(advice)) if bid != advice => (bid, advice),
+                _ => continue,
+            };
+            let won = record.winner == self.name;
+            // bids are in seat order and a tie goes to the earlier seat, as in the engine
+            let seat = record.bids.iter().position(|b| b.name == self.name).unwrap_or(0);
+            let coach_would_win = record.bids.iter().enumerate().filter(|(other, _)| *other != seat)
+                                        .all(|(other, b)| if other < seat { advice > b.offer } else { advice >= b.offer });
+            let prize = i64::from(record.prize_card);
+            let points = match (won, coach_would_win) {
+                (false, true) => prize,
+                (true, false) => -prize,
+                _ => 0,
+            };
+            lessons.push(Lesson{round: index + 1, prize_card: record.prize_card, bid, advice, points});
+        }
+        lessons
+    }
+
+    fn emit_history<W: Write>(&self, output: &mut W) {
+        if self.history.is_empty() {
+            let _ = writeln!(output, "no rounds played yet");
+        }
+        for (index, record) in self.history.iter().enumerate() {
+            let bids: Vec<String> = record.bids.iter().map(|b| format!("{} {}", b.name, b.offer)).collect();
+            let _ = writeln!(output, "round {}: prize {} bids [{}] winner {}",
+                             index + 1, record.prize_card, bids.join(", "), record.winner);
+        }
+    }
+
+    fn emit_scores<W: Write>(&self, output: &mut W) {
+        if self.scoreboard.is_empty() {
+            let _ = writeln!(output, "no rounds played yet");
+        }
+        for line in &self.scoreboard {
+            let _ = writeln!(output, "{}: {}", line.name, line.points);
+        }
+    }
+}
+
+impl Default for Console {
+    fn default() -> Console { Console::new() }
+}
+
+impl Bidder for Console {
+    fn select_card(&mut self, prize_card: u32, hand: &Hand, highest_card: u32) -> Option<u32> {
+        let _raw_mode = if self.tui { tui::RawMode::enable() } else { None };
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        self.prompt(prize_card, hand, highest_card, &mut stdin.lock(), &mut stdout.lock())
+    }
+
+    fn game_start(&mut self, name: &str, _num_players: u32, _num_cards: u32, _hand: &Hand) {
+        self.name = String::from(name);
+        self.history.clear();
+        self.mute_trace();
+    }
+
+    fn round_result(&mut self, prize_card: u32, bids: &[Offer], winner: &str) {
+        let advice = self.advice.take();
+        self.history.push(RoundRecord{prize_card, bids: bids.to_vec(), winner: String::from(winner), advice});
+    }
+
+    fn game_over(&mut self, _winner: &str, _scores: &[Score]) {
+        if let Some(coach) = &self.coach {
+            let stdout = io::stdout();
+            emit_review(coach, &self.lessons(), &mut stdout.lock());
+        }
+    }
+
+    fn show_scoreboard(&mut self, scoreboard: &[ScoreLine]) {
+        self.scoreboard = scoreboard.to_vec();
+    }
+
+    fn tourney_over(&mut self, _winner: &str, _scores: &[Score]) {
+        self.restore_trace();
+    }
+
+    fn has_resigned(&self) -> bool { self.resigned }
+
+    fn shutdown(&mut self) {
+        self.restore_trace();
+    }
+}
+
+// a terminal is cleared outright; otherwise (e.g. output is piped) scroll the old text away
+pub fn clear_screen<W: Write>(output: &mut W) {
+    if io::stdout().is_terminal() {
+        let _ = write!(output, "\x1b[2J\x1b[3J\x1b[H");
+    } else {
+        for _i in 1..40 {
+            let _ = writeln!(output);
+        }
+    }
+    let _ = output.flush();
+}
+
+fn emit_review<W: Write>(coach: &str, lessons: &[Lesson], output: &mut W) {
+    let _ = writeln!(output, "\ncoach review ({}):", coach);
+    if lessons.is_empty() {
+        let _ = writeln!(output, "you bid like the coach every round");
+        return;
+    }
+    for lesson in lessons {
+        let verdict = match lesson.points {
+            0 => String::from("same result"),
+            p if p > 0 => format!("cost you {} points", p),
+            p => format!("won you {} points", -p),
+        };
+        let _ = writeln!(output, "round {}: prize {}, you bid {}, coach bid {}: {}",
+                         lesson.round, lesson.prize_card, lesson.bid, lesson.advice, verdict);
+    }
+    let total: i64 = lessons.iter().map(|l| l.points).sum();
+    let _ = writeln!(output, "net points lost by not following the coach: {}", total);
+}
+
+pub fn validate_pick(pick: &str, hand: &Hand) -> Option<u32>  {
+    let guess: u32 = pick.trim().parse().ok()?;
+    match hand.cards.contains(&guess) {
+        false => None,
+        true => Some(guess),
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_validate_pick_ok() {
+        let pick = "8";
+        let hand = Hand{cards: vec![4,2,6,8]};
+        // test
+        let result = validate_pick(pick, &hand);
+
+        assert_eq!(result.unwrap(), 8);
+    }
+
+    #[test]
+    fn test_validate_pick_ok_newline() {
+        let pick = "8\n";
+        let hand = Hand{cards: vec![4,2,6,8]};
+        // test
+        let result = validate_pick(pick, &hand);
+
+        assert_eq!(result.unwrap(), 8);
+    }
+
+    #[test]
+    fn test_validate_pick_not_legal() {
+        let pick = "@2";
+        let hand = Hand{cards: vec![4,2,6,8]};
+        // test
+        let result = validate_pick(pick, &hand);
+
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_validate_pick_not_found() {
+        let pick = "7";
+        let hand = Hand{cards: vec![4,2,6,8]};
+        // test
+        let result = validate_pick(pick, &hand);
+
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_prompt_retries_bad_input() {
+        let hand = Hand{cards: vec![4,2,6,8]};
+        let mut console = Console::new();
+        let mut input = Cursor::new("@2\n7\nhelp\nhand\n6\n");
+        let mut output = vec![];
+        // test
+        let result = console.prompt(10, &hand, 12, &mut input, &mut output);
+
+        assert_eq!(result, Some(6));
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.contains("'@2' is not a card in your hand"));
+        assert!(text.contains("'7' is not a card in your hand"));
+    }
+
+    #[test]
+    fn test_prompt_eof_resigns() {
+        let hand = Hand{cards: vec![4,2,6,8]};
+        let mut console = Console::new();
+        let mut input = Cursor::new("");
+        let mut output = vec![];
+        // test
+        let result = console.prompt(10, &hand, 12, &mut input, &mut output);
+
+        assert_eq!(result, None);
+        assert!(console.resigned);
+    }
+
+    #[test]
+    fn test_prompt_quit_resigns() {
+        let hand = Hand{cards: vec![4,2,6,8]};
+        let mut console = Console::new();
+        let mut input = Cursor::new("quit\n6\n");
+        let mut output = vec![];
+        // test
+        let first = console.prompt(10, &hand, 12, &mut input, &mut output);
+        let second = console.prompt(10, &hand, 12, &mut input, &mut output);
+
+        assert_eq!(first, None);
+        assert_eq!(second, None);
+    }
+
+    #[test]
+    fn test_prompt_scores_from_scoreboard() {
+        let hand = Hand{cards: vec![4,2,6,8]};
+        let mut console = Console::new();
+        let line = |name: &str, points| ScoreLine{name: String::from(name), points, rounds_won: 1, games_won: 0};
+        console.scoreboard = vec![line("you", 17), line("bach", 4)];
+        let mut input = Cursor::new("scores\n6\n");
+        let mut output = vec![];
+        // test
+        let result = console.prompt(10, &hand, 12, &mut input, &mut output);
+
+        assert_eq!(result, Some(6));
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.contains("you: 17\nbach: 4\n"));
+    }
+
+    #[test]
+    fn test_prompt_shows_coach() {
+        let hand = Hand{cards: vec![4,2,6,8]};
+        let mut console = Console::new();
+        console.coach = Some(String::from("nearest_card"));
+        let mut input = Cursor::new("2\n");
+        let mut output = vec![];
+        // test
+        let result = console.prompt(7, &hand, 12, &mut input, &mut output);
+
+        assert_eq!(result, Some(2));
+        let text = String::from_utf8(output).unwrap();
+        assert!(text.contains("coach (nearest_card) bids 6"));
+    }
+
+    #[test]
+    fn test_lessons() {
+        let mut console = Console::new();
+        console.game_start("you", 2, 12, &Hand::new());
+        let offer = |name: &str, offer| Offer{name: String::from(name), offer};
+        // followed the coach
+        console.advice = Some(9);
+        console.round_result(10, &[offer("you", 9), offer("bach", 3)], "you");
+        // ignored the coach and lost a prize the coach's card would have won
+        console.advice = Some(8);
+        console.round_result(7, &[offer("you", 1), offer("bach", 5)], "bach");
+        // ignored the coach and won a prize the coach's card would have lost
+        console.advice = Some(2);
+        console.round_result(4, &[offer("you", 6), offer("bach", 4)], "you");
+        // test
+        let result = console.lessons();
+
+        assert_eq!(result, vec![
+            Lesson{round: 2, prize_card: 7, bid: 1, advice: 8, points: 7},
+            Lesson{round: 3, prize_card: 4, bid: 6, advice: 2, points: -4},
+        ]);
+    }
+
+    #[test]
+    fn test_lessons_tie_goes_to_earlier_seat() {
+        let mut console = Console::new();
+        console.game_start("you", 3, 12, &Hand::new());
+        let offer = |name: &str, offer| Offer{name: String::from(name), offer};
+        // the coach's 5 would tie bach, who sits first and keeps the prize
+        console.advice = Some(5);
+        console.round_result(7, &[offer("bach", 5), offer("you", 1), offer("liszt", 2)], "bach");
+        // the coach's 6 would tie liszt, who sits after you
+        console.advice = Some(6);
+        console.round_result(8, &[offer("bach", 3), offer("you", 2), offer("liszt", 6)], "liszt");
+        // test
+        let result = console.lessons();
+
+        assert_eq!(result, vec![
+            Lesson{round: 1, prize_card: 7, bid: 1, advice: 5, points: 0},
+            Lesson{round: 2, prize_card: 8, bid: 2, advice: 6, points: 8},
+        ]);
+    }
+
+    #[test]
+    fn test_prompt_hot_seat() {
+        let hand = Hand{cards: vec![4,2,6,8]};
+        let mut console = Console::new();
+        console.game_start("you", 2, 12, &Hand::new());
+        console.hot_seat = true;
+        let mut input = Cursor::new("\n6\n");
+        let mut output = vec![];
+        // test
+        let result = console.prompt(10, &hand, 12, &mut input, &mut output);
+
+        assert_eq!(result, Some(6));
+        let text = String::from_utf8(output).unwrap();
+        let pass = text.find("pass the keyboard to you").unwrap();
+        let shown = text.find("your hand: [4, 2, 6, 8]").unwrap();
+        assert!(pass < shown);
+        assert!(text.trim_end().ends_with("you has bid."));
+    }
+
+    #[test]
+    fn test_prompt_hot_seat_eof() {
+        let hand = Hand{cards: vec![4,2,6,8]};
+        let mut console = Console::new();
+        console.hot_seat = true;
+        let mut input = Cursor::new("");
+        let mut output = vec![];
+        // test
+        let result = console.prompt(10, &hand, 12, &mut input, &mut output);
+
+        assert_eq!(result, None);
+        assert!(console.resigned);
+        let text = String::from_utf8(output).unwrap();
+        assert!(!text.contains("your hand"));
+    }
+
+    #[test]
+    fn test_tui_mutes_trace_until_tourney_over() {
+        let hand = Hand{cards: vec![4,2,6,8]};
+        let mut console = Console{tui: true, .. Console::new()};
+        assert!(trace::enabled());
+
+        // test
+        console.game_start("you", 2, 12, &hand);
+        let muted = !trace::enabled();
+        console.tourney_over("you", &[]);
+
+        assert!(muted);
+        assert!(trace::enabled());
+    }
+}
@@ -0,0 +1,267 @@
+
+// a full-screen picker for the console: arrow keys move between the cards in
+// hand and Enter bids. std only, so the terminal is driven with ANSI escapes
+// and put into character-at-a-time mode with stty
+
+use std::collections::VecDeque;
+use std::io;
+use std::io::{IsTerminal, Read, Write};
+use std::process::{Command, Stdio};
+
+use crate::engine::Hand;
+use super::Console;
+
+const SELECTED: &str = "\x1b[7m";
+const PRIZE: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+const MAX_HISTORY: usize = 8;
+
+#[derive(Debug, PartialEq)]
+enum Key {
+    Left,
+    Right,
+    Enter,
+    Help,
+    Quit,
+    Other,
+    Eof,
+}
+
+// falls back to line mode when either end is piped
+pub fn available() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+// keys arrive one at a time without echo; the saved settings come back on drop
+pub struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    pub fn enable() -> Option<RawMode> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "min", "1"])?;
+        print!("\x1b[?25l");
+        let _ = io::stdout().flush();
+        Some(RawMode{saved: String::from(saved.trim())})
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        print!("\x1b[?25h");
+        let _ = io::stdout().flush();
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> Option<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).stderr(Stdio::null()).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
+
+// returns None once the player quits or input runs out
+pub fn choose<R: Read, W: Write>(console: &mut Console, prize_card: u32, hand: &Hand, highest_card: u32,
+                                 input: &mut R, output: &mut W) -> Option<u32> {
+    let advice = console.take_advice(prize_card, hand, highest_card);
+    let mut selected = 0;
+    let mut message = String::from("←/→ choose a card, Enter bids, ? help, q quits");
+    let mut keys = VecDeque::new();
+
+    loop {
+        let screen = render(console, prize_card, hand, selected, advice.as_deref(), &message);
+        let _ = write!(output, "{}", screen);
+        let _ = output.flush();
+
+        match next_key(input, &mut keys) {
+            Key::Left => selected = selected.saturating_sub(1),
+            Key::Right => selected = (selected + 1).min(hand.cards.len().saturating_sub(1)),
+            Key::Enter => return hand.cards.get(selected).copied(),
+            Key::Help => message = String::from("the highest bid wins the prize card; the most points wins the game"),
+            Key::Quit => {
+                let _ = writeln!(output, "\nforfeiting the rest of the tourney");
+                console.resigned = true;
+                return None;
+            }
+            Key::Eof => {
+                let _ = writeln!(output, "\nno more input: forfeiting the rest of the tourney");
+                console.resigned = true;
+                return None;
+            }
+            Key::Other => {}
+        }
+    }
+}
+
+// a terminal sends an arrow key's escape sequence in one go, so whatever one
+// read returns is parsed on its own: a lone Esc is a key, not the start of a sequence
+fn next_key<R: Read>(input: &mut R, keys: &mut VecDeque<Key>) -> Key {
+    if keys.is_empty() {
+        let mut bytes = [0u8; 64];
+        match input.read(&mut bytes) {
+            Ok(0) | Err(_) => return Key::Eof,
+            Ok(n) => keys.extend(parse_keys(&bytes[..n])),
+        }
+    }
+    keys.pop_front().unwrap_or(Key::Other)
+}
+
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        let key = match bytes[index] {
+            b'\n' | b'\r' => Key::Enter,
+            b'h' => Key::Left,
+            b'l' => Key::Right,
+            b'?' => Key::Help,
+            b'q' => Key::Quit,
+            0x1b if bytes.get(index + 1) == Some(&b'[') && index + 2 < bytes.len() => {
+                index += 2;
+                match bytes[index] {
+                    b'D' => Key::Left,
+                    b'C' => Key::Right,
+                    _ => Key::Other,
+                }
+            }
+            _ => Key::Other,
+        };
+        keys.push(key);
+        index += 1;
+    }
+    keys
+}
+
+fn render(console: &Console, prize_card: u32, hand: &Hand, selected: usize, advice: Option<&str>, message: &str) -> String {
+    let mut s = String::from("\x1b[2J\x1b[H");
+
+    s.push_str(&format!("{} — round {}\n\n", console.name, console.history.len() + 1));
+    s.push_str("prize card\n");
+    s.push_str(&tiles(&[prize_card], Some((0, PRIZE))));
+    s.push_str("\nyour hand\n");
+    s.push_str(&tiles(&hand.cards, Some((selected, SELECTED))));
+    if let Some(advice) = advice {
+        s.push_str(&format!("\n{}\n", advice));
+    }
+
+    s.push_str("\nprevious rounds\n");
+    if console.history.is_empty() {
+        s.push_str("  none yet\n");
+    }
+    let skipped = console.history.len().saturating_sub(MAX_HISTORY);
+    for (index, record) in console.history.iter().enumerate().skip(skipped) {
+        let bids: Vec<String> = record.bids.iter().map(|b| format!("{} {}", b.name, b.offer)).collect();
+        s.push_str(&format!("  {:>2}: prize {:>2}  [{}]  won by {}\n", index + 1, record.prize_card, bids.join(", "), record.winner));
+    }
+
+    s.push_str("\nscoreboard\n");
+    s.push_str(&format!("  {:<12} {:>6} {:>6} {:>6}\n", "player", "points", "rounds", "games"));
+    for line in &console.scoreboard {
+        s.push_str(&format!("  {:<12} {:>6} {:>6} {:>6}\n", line.name, line.points, line.rounds_won, line.games_won));
+    }
+
+    s.push_str(&format!("\n{}\n", message));
+    s
+}
+
+// one box per card, three lines tall; the marked card is drawn in the given style
+fn tiles(cards: &[u32], marked: Option<(usize, &str)>) -> String {
+    let mut rows = [String::new(), String::new(), String::new()];
+    for (index, card) in cards.iter().enumerate() {
+        let style = match marked {
+            Some((i, style)) if i == index => style,
+            _ => "",
+        };
+        let reset = if style.is_empty() { "" } else { RESET };
+        rows[0].push_str(&format!("{}┌────┐{} ", style, reset));
+        rows[1].push_str(&format!("{}│ {:>2} │{} ", style, card, reset));
+        rows[2].push_str(&format!("{}└────┘{} ", style, reset));
+    }
+    format!("  {}\n  {}\n  {}\n", rows[0], rows[1], rows[2])
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+    use super::super::{RoundRecord, ScoreLine};
+    use super::super::super::Bidder;
+    use super::super::super::external::Offer;
+
+    #[test]
+    fn test_choose_arrow_keys() {
+        let mut console = Console::new();
+        let hand = Hand{cards: vec![4, 2, 6, 8]};
+        let mut input: &[u8] = b"\x1b[C\x1b[C\x1b[D\x1b[C\x1b[C\n";
+        let mut output = vec![];
+
+        // test
+        let result = choose(&mut console, 10, &hand, 12, &mut input, &mut output);
+
+        assert_eq!(result, Some(8));
+    }
+
+    #[test]
+    fn test_choose_stops_at_edges() {
+        let mut console = Console::new();
+        let hand = Hand{cards: vec![4, 2]};
+        let mut input: &[u8] = b"hhhl\r";
+        let mut output = vec![];
+
+        // test
+        let result = choose(&mut console, 10, &hand, 12, &mut input, &mut output);
+
+        assert_eq!(result, Some(2));
+    }
+
+    #[test]
+    fn test_choose_eof_resigns() {
+        let mut console = Console::new();
+        let hand = Hand{cards: vec![4, 2]};
+        let mut input: &[u8] = b"\x1b[C";
+        let mut output = vec![];
+
+        // test
+        let result = choose(&mut console, 10, &hand, 12, &mut input, &mut output);
+
+        assert_eq!(result, None);
+        assert!(console.resigned);
+    }
+
+    #[test]
+    fn test_choose_lone_escape() {
+        let mut console = Console::new();
+        let hand = Hand{cards: vec![4, 2]};
+        let mut input: &[u8] = b"\x1bl\n";
+        let mut output = vec![];
+
+        // test
+        let result = choose(&mut console, 10, &hand, 12, &mut input, &mut output);
+
+        assert_eq!(result, Some(2));
+        assert!(!console.resigned);
+    }
+
+    #[test]
+    fn test_render_scoreboard() {
+        let mut console = Console::new();
+        console.game_start("alice", 2, 12, &Hand::new());
+        let bids = vec![Offer{name: String::from("alice"), offer: 9}, Offer{name: String::from("bach"), offer: 3}];
+        console.history.push(RoundRecord{prize_card: 7, bids, winner: String::from("alice"), advice: None});
+        // the table's count, which is what the scoreboard shows even when the history disagrees
+        console.scoreboard = vec![ScoreLine{name: String::from("alice"), points: 7, rounds_won: 1, games_won: 1},
+                                  ScoreLine{name: String::from("bach"), points: 0, rounds_won: 0, games_won: 2}];
+        let hand = Hand{cards: vec![4, 2]};
+
+        // test
+        let result = render(&console, 10, &hand, 1, None, "");
+
+        assert!(result.contains(&format!("{}│  2 │{}", SELECTED, RESET)));
+        assert!(result.contains(&format!("{}│ 10 │{}", PRIZE, RESET)));
+        assert!(result.contains("prize  7  [alice 9, bach 3]  won by alice"));
+        assert!(result.contains(&format!("  {:<12} {:>6} {:>6} {:>6}", "alice", 7, 1, 1)));
+        assert!(result.contains(&format!("  {:<12} {:>6} {:>6} {:>6}", "bach", 0, 0, 2)));
+    }
+}
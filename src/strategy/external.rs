@@ -0,0 +1,380 @@
+
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+use std::io::{BufRead, BufReader, PipeReader, PipeWriter, Write};
+use std::net::{Shutdown, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::Bidder;
+use super::console::ScoreLine;
+use crate::engine::Hand;
+
+pub const DEFAULT_TIMEOUT_MS: u64 = 1000;
+
+pub fn default_timeout_ms() -> u64 { DEFAULT_TIMEOUT_MS }
+
+// bump when a message changes shape; see ARENA.md
+pub const PROTOCOL_VERSION: u32 = 2;
+
+// the line-delimited JSON protocol: one message per line, engine -> bot.
+// only `bid_request` expects a reply, which is a single line: {"round": R, "bid": N},
+// R being the request's round so that a late reply cannot be taken for the next bid
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Message<'a> {
+    Seated { name: &'a str },
+    Registered { name: &'a str, protocol: u32 },
+    Rejected { reason: &'a str },
+    TourneyStart { table: u64, players: &'a [String], num_games: u32 },
+    GameStart { name: &'a str, num_players: u32, num_cards: u32, num_cards_per_hand: u32 },
+    Deal { hand: &'a [u32] },
+    PrizeRevealed { prize_card: u32, hand: &'a [u32] },
+    BidRequest { round: u64, prize_card: u32, max_card: u32, timeout_ms: u64 },
+    RoundResult { prize_card: u32, bids: &'a [Offer], winner: &'a str },
+    GameOver { winner: &'a str, scores: &'a [Score] },
+    // scores here are games won
+    TourneyOver { winner: &'a str, scores: &'a [Score] },
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Offer {
+    pub name: String,
+    pub offer: u32,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Score {
+    pub name: String,
+    pub total: u32,
+}
+
+// one line per message, in a single write so a socket sends it as one packet
+pub fn write_message<W: Write + ?Sized>(writer: &mut W, message: &Message) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).unwrap();
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()
+}
+
+#[derive(Debug, Deserialize)]
+struct Reply {
+    round: u64,
+    bid: u32,
+}
+
+#[derive(Debug)]
+pub enum ExternalError {
+    Io(io::Error),
+    Timeout,
+    Disconnected,
+    Malformed(String),
+}
+
+impl fmt::Display for ExternalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExternalError::Io(e) => write!(f, "i/o error: {}", e),
+            ExternalError::Timeout => write!(f, "timed out"),
+            ExternalError::Disconnected => write!(f, "bot exited"),
+            ExternalError::Malformed(line) => write!(f, "malformed reply: {:?}", line),
+        }
+    }
+}
+
+impl From<io::Error> for ExternalError {
+    fn from(e: io::Error) -> ExternalError { ExternalError::Io(e) }
+}
+
+// a bot on the other end of a pipe (a child process) or a socket (a remote seat)
+pub struct ExternalBot {
+    child: Option<Child>,
+    socket: Option<TcpStream>,
+    writer: Box<dyn Write + Send>,
+    replies: Receiver<String>,
+    timeout_ms: u64,
+    // when the bid asked for is due; set when it is asked, not when it is awaited
+    deadline: Option<Instant>,
+    // bid requests sent so far; the latest one's number is the round its reply must carry
+    round: u64,
+}
+
+impl fmt::Debug for ExternalBot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ExternalBot {{ child: {:?}, socket: {:?}, timeout_ms: {} }}", self.child, self.socket, self.timeout_ms)
+    }
+}
+
+impl ExternalBot {
+    pub fn spawn(command: &[String], timeout_ms: u64) -> Result<ExternalBot, ExternalError> {
+        let (program, args) = command.split_first()
+                                     .ok_or_else(|| ExternalError::Malformed(String::from("empty command")))?;
+        let mut child = Command::new(program)
+                            .args(args)
+                            .stdin(Stdio::piped())
+                            .stdout(Stdio::piped())
+                            .spawn()?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let mut bot = ExternalBot::start(BufReader::new(stdout), Box::new(stdin), timeout_ms);
+        bot.child = Some(child);
+        Ok(bot)
+    }
+
+    // the reader may already hold buffered input, e.g. after a join handshake
+    pub fn from_socket(reader: BufReader<TcpStream>, timeout_ms: u64) -> Result<ExternalBot, ExternalError> {
+        reader.get_ref().set_nodelay(true)?;
+        let writer = reader.get_ref().try_clone()?;
+        let socket = reader.get_ref().try_clone()?;
+
+        let mut bot = ExternalBot::start(reader, Box::new(writer), timeout_ms);
+        bot.socket = Some(socket);
+        Ok(bot)
+    }
+
+    // for a seat played from inside this process, e.g. over HTTP
+    pub fn from_pipes(reader: PipeReader, writer: PipeWriter, timeout_ms: u64) -> ExternalBot {
+        ExternalBot::start(BufReader::new(reader), Box::new(writer), timeout_ms)
+    }
+
+    fn start<R: BufRead + Send + 'static>(reader: R, writer: Box<dyn Write + Send>, timeout_ms: u64) -> ExternalBot {
+        // a reader thread lets us wait on the bot with a timeout
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in reader.lines() {
+                match line {
+                    Ok(line) => if sender.send(line).is_err() { break; },
+                    Err(_) => break,
+                }
+            }
+        });
+
+        ExternalBot{child: None, socket: None, writer, replies, timeout_ms, deadline: None, round: 0}
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<(), ExternalError> {
+        write_message(&mut self.writer, message)?;
+        Ok(())
+    }
+
+    fn notify(&mut self, message: &Message) {
+        if let Err(e) = self.send(message) {
+            tracer!("TRACER external bot unreachable: {}", e);
+        }
+    }
+
+    // the bid is not checked against the hand here: the table's GameState enforces legality
+    pub fn request_bid(&mut self, prize_card: u32, hand: &[u32], max_card: u32) -> Result<u32, ExternalError> {
+        if self.deadline.is_none() {
+            self.ask_bid(prize_card, hand, max_card)?;
+        }
+        self.await_bid()
+    }
+
+    // asking every bot before awaiting any of them lets them all think at once,
+    // and the whole round then takes no longer than the slowest timeout
+    pub fn ask_bid(&mut self, prize_card: u32, hand: &[u32], max_card: u32) -> Result<(), ExternalError> {
+        // discard late replies to earlier requests that timed out
+        while self.replies.try_recv().is_ok() {}
+
+        self.round += 1;
+        self.send(&Message::PrizeRevealed{prize_card, hand})?;
+        self.send(&Message::BidRequest{round: self.round, prize_card, max_card, timeout_ms: self.timeout_ms})?;
+        self.deadline = Some(Instant::now() + Duration::from_millis(self.timeout_ms));
+        Ok(())
+    }
+
+    fn await_bid(&mut self) -> Result<u32, ExternalError> {
+        let deadline = self.deadline.take().unwrap_or_else(|| Instant::now() + Duration::from_millis(self.timeout_ms));
+        loop {
+            let line = match self.replies.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => return Err(ExternalError::Timeout),
+                Err(RecvTimeoutError::Disconnected) => return Err(ExternalError::Disconnected),
+            };
+            let reply: Reply = serde_json::from_str(line.trim()).map_err(|_| ExternalError::Malformed(line.clone()))?;
+            // a reply to an earlier round arrived too late to count
+            if reply.round == self.round {
+                return Ok(reply.bid);
+            }
+            tracer!("TRACER external bot answered round {} in round {}", reply.round, self.round);
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout_ms: u64) -> ExternalBot {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    // false once the other end has hung up
+    pub fn is_alive(&mut self) -> bool {
+        !matches!(self.replies.try_recv(), Err(TryRecvError::Disconnected))
+    }
+
+    pub fn shutdown(&mut self) {
+        if let Some(child) = &mut self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        if let Some(socket) = &self.socket {
+            let _ = socket.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+// a bot that stops answering is traced, and its next bid times out
+impl Bidder for ExternalBot {
+    fn select_card(&mut self, prize_card: u32, hand: &Hand, highest_card: u32) -> Option<u32> {
+        match self.request_bid(prize_card, &hand.cards, highest_card) {
+            Ok(card) => Some(card),
+            Err(e) => {
+                tracer!("TRACER external bot offers no card: {}", e);
+                None
+            }
+        }
+    }
+
+    fn reveal_prize(&mut self, prize_card: u32, hand: &Hand, highest_card: u32) {
+        if let Err(e) = self.ask_bid(prize_card, &hand.cards, highest_card) {
+            tracer!("TRACER external bot unreachable: {}", e);
+        }
+    }
+
+    fn game_start(&mut self, name: &str, num_players: u32, num_cards: u32, hand: &Hand) {
+        let num_cards_per_hand = u32::try_from(hand.cards.len()).unwrap();
+        self.notify(&Message::GameStart{name, num_players, num_cards, num_cards_per_hand});
+        self.notify(&Message::Deal{hand: &hand.cards});
+    }
+
+    fn round_result(&mut self, prize_card: u32, bids: &[Offer], winner: &str) {
+        self.notify(&Message::RoundResult{prize_card, bids, winner});
+    }
+
+    fn game_over(&mut self, winner: &str, scores: &[Score]) {
+        self.notify(&Message::GameOver{winner, scores});
+    }
+
+    // bots keep their own score
+    fn show_scoreboard(&mut self, _scoreboard: &[ScoreLine]) {}
+
+    fn tourney_over(&mut self, winner: &str, scores: &[Score]) {
+        self.notify(&Message::TourneyOver{winner, scores});
+    }
+
+    fn shutdown(&mut self) {
+        ExternalBot::shutdown(self);
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    fn shell_bot(script: &str) -> ExternalBot {
+        let command = vec![String::from("sh"), String::from("-c"), String::from(script)];
+        ExternalBot::spawn(&command, 500).unwrap()
+    }
+
+    #[test]
+    fn test_request_bid_ok() {
+        let mut bot = shell_bot(r#"while read line; do case "$line" in *bid_request*) echo '{"round": 1, "bid": 6}';; esac; done"#);
+        let hand = vec![4,2,6,8];
+        // test
+        let result = bot.request_bid(10, &hand, 12);
+
+        assert_eq!(result.unwrap(), 6);
+    }
+
+    #[test]
+    fn test_request_bid_not_in_hand() {
+        let mut bot = shell_bot(r#"while read line; do case "$line" in *bid_request*) echo '{"round": 1, "bid": 7}';; esac; done"#);
+        let hand = vec![4,2,6,8];
+        // test
+        let result = bot.request_bid(10, &hand, 12);
+
+        assert_eq!(result.unwrap(), 7);
+    }
+
+    #[test]
+    fn test_request_bid_malformed() {
+        let mut bot = shell_bot(r#"while read line; do case "$line" in *bid_request*) echo 'six';; esac; done"#);
+        let hand = vec![4,2,6,8];
+        // test
+        let result = bot.request_bid(10, &hand, 12);
+
+        assert!(matches!(result, Err(ExternalError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_request_bid_no_round() {
+        let mut bot = shell_bot(r#"while read line; do case "$line" in *bid_request*) echo '{"bid": 6}';; esac; done"#);
+        let hand = vec![4,2,6,8];
+        // test
+        let result = bot.request_bid(10, &hand, 12);
+
+        assert!(matches!(result, Err(ExternalError::Malformed(_))));
+    }
+
+    #[test]
+    fn test_request_bid_drops_late_reply() {
+        // answers round 1 only after round 2 has been asked, then answers round 2
+        let mut bot = shell_bot(r#"n=0; while read line; do case "$line" in *bid_request*) n=$((n+1)); if [ $n -eq 2 ]; then echo '{"round": 1, "bid": 4}'; echo '{"round": 2, "bid": 8}'; fi;; esac; done"#);
+        let hand = vec![4,2,6,8];
+        assert!(matches!(bot.request_bid(10, &hand, 12), Err(ExternalError::Timeout)));
+
+        // test
+        let result = bot.request_bid(11, &hand, 12);
+
+        assert_eq!(result.unwrap(), 8);
+    }
+
+    #[test]
+    fn test_request_bid_timeout() {
+        let mut bot = shell_bot("while read line; do :; done");
+        let hand = vec![4,2,6,8];
+        // test
+        let result = bot.request_bid(10, &hand, 12);
+
+        assert!(matches!(result, Err(ExternalError::Timeout)));
+    }
+
+    #[test]
+    fn test_await_bid_shares_the_deadline() {
+        let mut bots = vec![shell_bot("while read line; do :; done"), shell_bot("while read line; do :; done")];
+        let hand = vec![4,2,6,8];
+        for bot in &mut bots {
+            bot.ask_bid(10, &hand, 12).unwrap();
+        }
+        let start = Instant::now();
+
+        // test
+        for bot in &mut bots {
+            assert!(matches!(bot.request_bid(10, &hand, 12), Err(ExternalError::Timeout)));
+        }
+
+        // both timed out together, not one after the other
+        assert!(start.elapsed() < Duration::from_millis(900));
+    }
+
+    #[test]
+    fn test_message_format() {
+        let hand = vec![4,2];
+        // test
+        let result = serde_json::to_string(&Message::PrizeRevealed{prize_card: 10, hand: &hand}).unwrap();
+
+        assert_eq!(result, r#"{"type":"prize_revealed","prize_card":10,"hand":[4,2]}"#);
+    }
+}
@@ -0,0 +1,379 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+
+use crate::config;
+use crate::config::{Config, JsonPlayer};
+use crate::engine;
+use crate::game;
+use crate::game::Table;
+use crate::interrupt;
+use crate::player::Player;
+use crate::ratings;
+use crate::ratings::{Ratings, RatingsConfig};
+use crate::strategy;
+
+// a pool of entrants, more than fit at one table, played over rounds of several tables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TournamentConfig {
+    pub format: Format,
+    // round robin plays until every pair has met, unless capped here; swiss defaults to log2 of the entrants
+    #[serde(default)]
+    pub num_rounds: Option<u32>,
+    pub table_size: u32,
+    // games per table per round
+    pub num_games: u32,
+    pub num_cards: u32,
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub ratings: Option<RatingsConfig>,
+    pub(crate) entrants: Vec<JsonPlayer>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Format {
+    RoundRobin,
+    Swiss,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub strategy: String,
+    // at each table: 1 for every opponent with fewer games won, 1/2 for a tie, over the number of opponents
+    pub points: f64,
+    pub tables: u32,
+    pub games_played: u32,
+    pub games_won: u32,
+}
+
+#[derive(Debug)]
+pub struct Standings(pub Vec<Standing>);
+
+impl Standings {
+    // most points first, then most games won
+    pub fn ranked(&self) -> Vec<&Standing> {
+        let mut ranked: Vec<&Standing> = self.0.iter().collect();
+        ranked.sort_by(|a, b| b.points.total_cmp(&a.points).then(b.games_won.cmp(&a.games_won)).then(a.name.cmp(&b.name)));
+        ranked
+    }
+}
+
+impl fmt::Display for Standings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (place, s) in self.ranked().iter().enumerate() {
+            writeln!(f, "  {:>3}. {} ({}) {:.2} points, {} tables, won {} of {} games",
+                     place + 1, s.name, s.strategy, s.points, s.tables, s.games_won, s.games_played)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn build_from_json(config_file: &str) -> Result<TournamentConfig, String> {
+    let data = fs::read_to_string(config_file).map_err(|e| format!("unable to read {}: {}", config_file, e))?;
+    serde_json::from_str(&data).map_err(|e| e.to_string())
+}
+
+fn validate(tournament_config: &TournamentConfig) -> Result<(), String> {
+    let entrants = &tournament_config.entrants;
+    if tournament_config.table_size < 2 {
+        return Err(String::from("table_size must be at least 2"));
+    }
+    if entrants.len() < 2 {
+        return Err(String::from("at least two entrants are required"));
+    }
+    if tournament_config.num_games == 0 {
+        return Err(String::from("num_games must be at least 1"));
+    }
+    let largest_table = (tournament_config.table_size as usize).min(entrants.len());
+    config::check_num_cards(tournament_config.num_cards, largest_table)?;
+    let mut names = HashSet::new();
+    for entrant in entrants {
+        if !names.insert(&entrant.name) {
+            return Err(format!("{} is entered twice", entrant.name));
+        }
+        // nobody at the console, and no seat that waits for a client that is never coming
+        if [strategy::CONSOLE, strategy::REMOTE, strategy::API].contains(&entrant.strategy.as_str()) {
+            return Err(format!("{} cannot play in a tournament as {}", entrant.name, entrant.strategy));
+        }
+    }
+    Ok(())
+}
+
+// as even as possible: 22 entrants at tables of 4 sit 4, 4, 4, 4, 3, 3
+fn table_sizes(num_entrants: usize, table_size: usize) -> Vec<usize> {
+    let num_tables = num_entrants.div_ceil(table_size);
+    let smaller = num_entrants / num_tables;
+    let num_larger = num_entrants % num_tables;
+    (0..num_tables).map(|t| if t < num_larger { smaller + 1 } else { smaller }).collect()
+}
+
+fn num_unmet(entrant: usize, meetings: &[Vec<u32>]) -> usize {
+    meetings[entrant].iter().enumerate().filter(|(other, met)| *other != entrant && **met == 0).count()
+}
+
+// greedy: open each table with whoever has the most opponents left to meet, then add whoever meets the most new faces
+fn round_robin_round(meetings: &[Vec<u32>], sizes: &[usize]) -> Vec<Vec<usize>> {
+    let mut free: Vec<usize> = (0..meetings.len()).collect();
+    let mut tables = vec![];
+    for size in sizes {
+        let first = *free.iter().max_by_key(|e| (num_unmet(**e, meetings), std::cmp::Reverse(**e))).unwrap();
+        free.retain(|e| *e != first);
+        let mut table = vec![first];
+        while table.len() < *size {
+            let next = *free.iter().max_by_key(|e| {
+                let new_faces = table.iter().filter(|t| meetings[**e][**t] == 0).count();
+                (new_faces, num_unmet(**e, meetings), std::cmp::Reverse(**e))
+            }).unwrap();
+            free.retain(|e| *e != next);
+            table.push(next);
+        }
+        tables.push(table);
+    }
+    tables
+}
+
+// tables of neighbours in the standings, avoiding rematches where the next few allow it
+fn swiss_round(standings: &Standings, meetings: &[Vec<u32>], sizes: &[usize]) -> Vec<Vec<usize>> {
+    let ranked = standings.ranked();
+    let mut free: Vec<usize> = ranked.iter().map(|s| standings.0.iter().position(|x| x.name == s.name).unwrap()).collect();
+    let mut tables = vec![];
+    for size in sizes {
+        let mut table = vec![free.remove(0)];
+        while table.len() < *size {
+            let position = (0..free.len()).min_by_key(|p| {
+                let rematches: u32 = table.iter().map(|t| meetings[free[*p]][*t]).sum();
+                (rematches, *p)
+            }).unwrap();
+            table.push(free.remove(position));
+        }
+        tables.push(table);
+    }
+    tables
+}
+
+fn all_met(meetings: &[Vec<u32>]) -> bool {
+    (0..meetings.len()).all(|e| num_unmet(e, meetings) == 0)
+}
+
+// plays one table's tourney with fresh stats; the players go back to the pool afterwards, bots still running
+fn play_table(tournament_config: &TournamentConfig, seed: u64, mut players: Vec<Player>, ratings: &mut Option<Ratings>) -> Vec<Player> {
+    for player in &mut players {
+        player.reset_stats();
+    }
+    let num_players = players.len() as u32;
+    let config = Config::new(num_players, tournament_config.num_games, tournament_config.num_cards);
+    let mut table = Table{players, seed, ratings: ratings.take(), .. Table::new()};
+    game::run_quiet_tourney(&config, &mut table);
+    *ratings = table.ratings.take();
+    table.players
+}
+
+pub fn run(tournament_config: &TournamentConfig) -> Result<Standings, String> {
+    validate(tournament_config)?;
+
+    let entrants = &tournament_config.entrants;
+    let n = entrants.len();
+    let sizes = table_sizes(n, tournament_config.table_size as usize);
+    let tourney_seed = tournament_config.seed.unwrap_or_else(rand::random);
+    let num_rounds = match (tournament_config.format, tournament_config.num_rounds) {
+        (_, Some(num_rounds)) => num_rounds,
+        (Format::RoundRobin, None) => u32::MAX,
+        (Format::Swiss, None) => (n as f64).log2().ceil() as u32,
+    };
+
+    let mut pool: Vec<Option<Player>> = vec![];
+    for entrant in entrants {
+        pool.push(Some(entrant.clone().into_player()?));
+    }
+    let mut standings = Standings(entrants.iter().map(|e| Standing{
+        name: e.name.clone(), strategy: e.strategy.clone(), points: 0.0, tables: 0, games_played: 0, games_won: 0,
+    }).collect());
+    let mut meetings = vec![vec![0; n]; n];
+    let mut num_tables = 0;
+    let mut ratings = match &tournament_config.ratings {
+        Some(ratings_config) => Some(Ratings::load(ratings_config.clone())?),
+        None => None,
+    };
+
+    for round in 0..num_rounds {
+        if tournament_config.format == Format::RoundRobin && all_met(&meetings) {
+            break;
+        }
+        let tables = match tournament_config.format {
+            Format::RoundRobin => round_robin_round(&meetings, &sizes),
+            Format::Swiss => swiss_round(&standings, &meetings, &sizes),
+        };
+
+        for (table_index, seats) in tables.iter().enumerate() {
+            if interrupt::requested() {
+                break;
+            }
+            let names: Vec<&str> = seats.iter().map(|e| entrants[*e].name.as_str()).collect();
+            tracer!("TRACER tournament round {} table {}: {}", round + 1, table_index + 1, names.join(", "));
+
+            let players = seats.iter().map(|e| pool[*e].take().unwrap()).collect();
+            let players = play_table(tournament_config, engine::game_seed(tourney_seed, num_tables), players, &mut ratings);
+            num_tables += 1;
+
+            let games_played = players[0].player_stats().num_games_played();
+            let games_won: Vec<u32> = players.iter().map(|p| p.num_games_won()).collect();
+            for (player, e) in players.into_iter().zip(seats) {
+                pool[*e] = Some(player);
+            }
+            let num_opponents = (seats.len() - 1) as f64;
+            for (i, a) in seats.iter().enumerate() {
+                let mut beaten = 0.0;
+                for (j, b) in seats.iter().enumerate() {
+                    if i != j {
+                        meetings[*a][*b] += 1;
+                        beaten += if games_won[i] > games_won[j] { 1.0 } else if games_won[i] == games_won[j] { 0.5 } else { 0.0 };
+                    }
+                }
+                let standing = &mut standings.0[*a];
+                standing.points += beaten / num_opponents;
+                standing.tables += 1;
+                standing.games_played += games_played;
+                standing.games_won += games_won[i];
+            }
+        }
+    }
+
+    for player in pool.iter_mut().flatten() {
+        player.leave_table();
+    }
+    Ok(standings)
+}
+
+pub fn play(config_file: &str) {
+    let tournament_config = match build_from_json(config_file) {
+        Ok(tournament_config) => tournament_config,
+        Err(e) => {
+            println!("tournament cannot start: {}", e);
+            return;
+        }
+    };
+    interrupt::install();
+    match run(&tournament_config) {
+        Ok(standings) => {
+            let status = if interrupt::requested() { "interrupted" } else { "complete" };
+            println!("\n\ntournament {}. standings:\n{}", status, standings);
+            if let Some(ratings_config) = &tournament_config.ratings {
+                ratings::show(&ratings_config.file);
+            }
+        }
+        Err(e) => println!("tournament cannot start: {}", e),
+    }
+}
+
+#[allow(unused_imports)]
+mod tests {
+    use super::*;
+
+    #[allow(dead_code)]
+    fn tournament(format: &str, num_entrants: usize, table_size: u32, num_rounds: Option<u32>) -> TournamentConfig {
+        let strategies = ["hybrid_card", "max_card", "min_card", "nearest_card", "next_card"];
+        let entrants: Vec<String> = (0..num_entrants)
+            .map(|i| format!(r#"{{"name": "bot{}", "strategy": "{}"}}"#, i, strategies[i % strategies.len()]))
+            .collect();
+        let num_rounds = num_rounds.map(|r| r.to_string()).unwrap_or(String::from("null"));
+        let data = format!(r#"{{"format": "{}", "num_rounds": {}, "table_size": {}, "num_games": 3, "num_cards": 20, "seed": 5,
+                               "entrants": [{}]}}"#, format, num_rounds, table_size, entrants.join(", "));
+        serde_json::from_str(&data).unwrap()
+    }
+
+    #[test]
+    fn test_table_sizes_basic() {
+        // test
+        let result = table_sizes(22, 4);
+
+        assert_eq!(result, vec![4, 4, 4, 4, 3, 3]);
+    }
+
+    #[test]
+    fn test_round_robin_everyone_meets() {
+        let sizes = table_sizes(10, 4);
+        let mut meetings = vec![vec![0; 10]; 10];
+
+        // test
+        for _round in 0..20 {
+            if all_met(&meetings) {
+                break;
+            }
+            for seats in round_robin_round(&meetings, &sizes) {
+                for a in &seats {
+                    for b in &seats {
+                        if a != b {
+                            meetings[*a][*b] += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        assert!(all_met(&meetings));
+    }
+
+    #[test]
+    fn test_run_swiss() {
+        let tournament_config = tournament("swiss", 7, 3, Some(2));
+
+        // test
+        let result = run(&tournament_config).unwrap();
+
+        assert!(result.0.iter().all(|s| s.tables == 2 && s.games_played == 6));
+        // every table hands out half a point per seat
+        let points: f64 = result.0.iter().map(|s| s.points).sum();
+        assert!((points - 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_run_rejects_duplicates() {
+        let mut tournament_config = tournament("round_robin", 3, 2, None);
+        tournament_config.entrants[2].name = String::from("bot0");
+
+        // test
+        let result = run(&tournament_config);
+
+        assert_eq!(result.unwrap_err(), "bot0 is entered twice");
+    }
+
+    #[test]
+    fn test_play_table_fresh_stats() {
+        let tournament_config = tournament("swiss", 2, 2, None);
+        let mut players: Vec<Player> = tournament_config.entrants.iter().map(|e| e.clone().into_player().unwrap()).collect();
+        players[0].wins_game();
+        players[1].disqualify();
+
+        // test
+        let result = play_table(&tournament_config, 11, players, &mut None);
+
+        assert!(result.iter().all(|p| p.player_stats().num_games_played() == 3 && !p.is_disqualified()));
+        assert_eq!(result.iter().map(|p| p.num_games_won()).sum::<u32>(), 3);
+    }
+
+    #[test]
+    fn test_run_rejects_too_few_cards() {
+        let mut tournament_config = tournament("round_robin", 5, 4, None);
+        tournament_config.num_cards = 4;
+
+        // test
+        let result = run(&tournament_config);
+
+        assert_eq!(result.unwrap_err(), "num_cards must be at least 5 for 4 players and the kitty");
+    }
+
+    #[test]
+    fn test_run_rejects_api_entrant() {
+        let mut tournament_config = tournament("round_robin", 3, 2, None);
+        tournament_config.entrants[1].strategy = String::from("api");
+
+        // test
+        let result = run(&tournament_config);
+
+        assert_eq!(result.unwrap_err(), "bot1 cannot play in a tournament as api");
+    }
+}
@@ -0,0 +1,33 @@
+// the TRACER lines that follow a game on stdout. a full-screen console turns
+// them off, so they do not scribble over its screen, and a quiet table keeps
+// its own games out of them
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+// println! for TRACER lines
+macro_rules! tracer {
+    ($($arg:tt)*) => {
+        if $crate::trace::enabled() {
+            println!($($arg)*);
+        }
+    };
+}
+
+// tracer! for the games at a table, which says nothing while the table is quiet
+macro_rules! table_tracer {
+    ($table:expr, $($arg:tt)*) => {
+        if !$table.quiet {
+            tracer!($($arg)*);
+        }
+    };
+}
@@ -0,0 +1,102 @@
+
+// exports for the browser page in web/. wasm32-unknown-unknown has no
+// bindings generator here, so calls take and return plain numbers, and the
+// state is handed over as JSON text the page reads out of linear memory
+
+use serde_json::json;
+use std::cell::RefCell;
+
+use crate::engine::builtin;
+use crate::engine::GameState;
+
+const HUMAN: usize = 0;
+
+struct Session {
+    game: GameState,
+    strategies: Vec<&'static str>,
+    state: String,
+}
+
+thread_local! {
+    static SESSION: RefCell<Option<Session>> = const { RefCell::new(None) };
+}
+
+// seat 0 is the human; each bot takes the next built-in strategy in turn
+#[no_mangle]
+pub extern "C" fn waro_new_game(num_cards: u32, num_bots: u32, seed: u32) {
+    let num_bots = num_bots.max(1) as usize;
+    let strategies = (0..num_bots).map(|i| builtin::NAMES[i % builtin::NAMES.len()]).collect();
+    let mut game = GameState::new(num_bots + 1, num_cards, u64::from(seed));
+    game.reveal_prize();
+    SESSION.with(|s| *s.borrow_mut() = Some(Session{game, strategies, state: String::new()}));
+}
+
+// returns 0 if the bid was played, -1 if it was not legal
+#[no_mangle]
+pub extern "C" fn waro_bid(card: u32) -> i32 {
+    SESSION.with(|s| {
+        let mut session = s.borrow_mut();
+        let session = match session.as_mut() {
+            Some(session) => session,
+            None => return -1,
+        };
+        let game = &mut session.game;
+        if game.submit_bid(HUMAN, card).is_err() {
+            return -1;
+        }
+
+        let prize_card = game.prize_card().unwrap_or(0);
+        for (index, strategy) in session.strategies.iter().enumerate() {
+            let seat = index + 1;
+            let _ = match builtin::built_in_card(strategy, prize_card, game.hand(seat), game.num_cards) {
+                Some(bid) => game.submit_bid(seat, bid),
+                None => game.forfeit(seat),
+            };
+        }
+        game.resolve_round();
+        game.reveal_prize();
+        0
+    })
+}
+
+// the current state as JSON; call waro_state_len for its length
+#[no_mangle]
+pub extern "C" fn waro_state() -> *const u8 {
+    SESSION.with(|s| {
+        let mut session = s.borrow_mut();
+        match session.as_mut() {
+            Some(session) => {
+                session.state = state_json(session);
+                session.state.as_ptr()
+            }
+            None => std::ptr::null(),
+        }
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn waro_state_len() -> usize {
+    SESSION.with(|s| s.borrow().as_ref().map_or(0, |session| session.state.len()))
+}
+
+fn state_json(session: &Session) -> String {
+    let game = &session.game;
+    let mut players = vec![String::from("you")];
+    players.extend(session.strategies.iter().enumerate().map(|(i, s)| format!("bot {} ({})", i + 1, s)));
+    let rounds: Vec<_> = game.rounds().iter().map(|r| json!({
+        "prize_card": r.prize_card,
+        "bids": r.bids,
+        "winner": r.winner,
+    })).collect();
+
+    json!({
+        "players": players,
+        "hand": game.hand(HUMAN).cards,
+        "prize_card": game.prize_card(),
+        "kitty_size": game.kitty().cards.len(),
+        "scores": game.scores(),
+        "rounds": rounds,
+        "finished": game.is_finished(),
+        "winner": game.winner(),
+    }).to_string()
+}